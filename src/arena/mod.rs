@@ -0,0 +1,193 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// COLLISION GROUPS -------------------------------------
+
+pub const WALL_GROUP: Group = Group::GROUP_1;
+pub const PLAYER_GROUP: Group = Group::GROUP_2;
+/// Missiles only ever touch walls and holes.
+pub const MISSILE_GROUP: Group = Group::GROUP_3;
+pub const HOLE_GROUP: Group = Group::GROUP_4;
+pub const STAR_GROUP: Group = Group::GROUP_5;
+
+/// Strip along the top of the arena kept clear for the HUD.
+pub const HUD_HEIGHT: f32 = 40.0;
+const WALL_THICKNESS: f32 = 10.0;
+
+// COMPONENTS -------------------------------------
+
+/// One of the four boundary walls around the arena.
+#[derive(Component, Clone, Copy, Debug)]
+pub enum ArenaWall {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl ArenaWall {
+    pub const ALL: [ArenaWall; 4] = [
+        ArenaWall::Top,
+        ArenaWall::Bottom,
+        ArenaWall::Left,
+        ArenaWall::Right,
+    ];
+
+    /// Where the wall sits and its shape for the given bounds.
+    pub fn placement(self, bounds: &ArenaBounds) -> (Transform, Collider) {
+        let half_width = bounds.half_width();
+        let half_height = bounds.half_height();
+        let half_thickness = WALL_THICKNESS / 2.0;
+        match self {
+            ArenaWall::Top => (
+                Transform::from_xyz(0.0, half_height - HUD_HEIGHT + half_thickness, 0.0),
+                Collider::cuboid(half_width, half_thickness),
+            ),
+            ArenaWall::Bottom => (
+                Transform::from_xyz(0.0, -half_height - half_thickness, 0.0),
+                Collider::cuboid(half_width, half_thickness),
+            ),
+            ArenaWall::Left => (
+                Transform::from_xyz(-half_width - half_thickness, 0.0, 0.0),
+                Collider::cuboid(half_thickness, half_height),
+            ),
+            ArenaWall::Right => (
+                Transform::from_xyz(half_width + half_thickness, 0.0, 0.0),
+                Collider::cuboid(half_thickness, half_height),
+            ),
+        }
+    }
+}
+
+/// The background quad, stretched over the whole arena.
+#[derive(Component)]
+pub struct ArenaBackground;
+
+/// Moved back inside the walls when the arena shrinks around it.
+#[derive(Component)]
+pub struct KeepInArena {
+    pub radius: f32,
+}
+
+// RESOURCES -------------------------------------
+
+/// Size of the playable area in world units.
+///
+/// Gameplay systems read this instead of the `Window` so they keep working
+/// when the app runs without one (see `HeadlessPlugin`).
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArenaBounds {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for ArenaBounds {
+    fn default() -> Self {
+        ArenaBounds {
+            width: 1280.0,
+            height: 720.0,
+        }
+    }
+}
+
+impl ArenaBounds {
+    pub fn half_width(&self) -> f32 {
+        self.width / 2.0
+    }
+
+    pub fn half_height(&self) -> f32 {
+        self.height / 2.0
+    }
+}
+
+/// While present, `ArenaBounds` keeps its size and ignores the window. Used
+/// to play a replay back in the arena it was recorded in.
+#[derive(Resource)]
+pub struct PinnedArenaBounds;
+
+// COLLISIONS -------------------------------------
+
+/// The two entities of a collision that just started, ordered so the first
+/// one passes `is_first`. `None` for stopped collisions or when neither does.
+pub fn started_with(
+    collision: &CollisionEvent,
+    is_first: impl Fn(Entity) -> bool,
+) -> Option<(Entity, Entity)> {
+    let CollisionEvent::Started(a, b, _) = collision else {
+        return None;
+    };
+    if is_first(*a) {
+        Some((*a, *b))
+    } else if is_first(*b) {
+        Some((*b, *a))
+    } else {
+        None
+    }
+}
+
+// SYSTEMS -------------------------------------
+
+fn sync_arena_bounds(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut bounds: ResMut<ArenaBounds>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    // A minimized window reports a zero size; keep the last real one.
+    if window.width() < 1.0 || window.height() < 1.0 {
+        return;
+    }
+    bounds.set_if_neq(ArenaBounds {
+        width: window.width(),
+        height: window.height(),
+    });
+}
+
+fn fit_walls(
+    bounds: Res<ArenaBounds>,
+    mut walls: Query<(&ArenaWall, &mut Transform, &mut Collider)>,
+) {
+    for (wall, mut transform, mut collider) in walls.iter_mut() {
+        (*transform, *collider) = wall.placement(&bounds);
+    }
+}
+
+fn fit_background(
+    bounds: Res<ArenaBounds>,
+    mut backgrounds: Query<&mut Transform, With<ArenaBackground>>,
+) {
+    for mut transform in backgrounds.iter_mut() {
+        transform.scale = Vec3::new(bounds.width, bounds.height, 1.0);
+    }
+}
+
+fn keep_in_arena(bounds: Res<ArenaBounds>, mut bodies: Query<(&KeepInArena, &mut Transform)>) {
+    for (body, mut transform) in bodies.iter_mut() {
+        let max_x = (bounds.half_width() - body.radius).max(0.0);
+        let min_y = -bounds.half_height() + body.radius;
+        let max_y = (bounds.half_height() - HUD_HEIGHT - body.radius).max(min_y);
+        transform.translation.x = transform.translation.x.clamp(-max_x, max_x);
+        transform.translation.y = transform.translation.y.clamp(min_y, max_y);
+    }
+}
+
+// PLUGIN -------------------------------------
+
+/// Tracks the window size and refits the walls, the background and anything
+/// marked `KeepInArena` when it changes, without touching the run itself.
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ArenaBounds>().add_systems(
+            PreUpdate,
+            (
+                sync_arena_bounds.run_if(not(resource_exists::<PinnedArenaBounds>)),
+                (fit_walls, fit_background, keep_in_arena).run_if(resource_changed::<ArenaBounds>),
+            )
+                .chain(),
+        );
+    }
+}
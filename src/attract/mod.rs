@@ -0,0 +1,167 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::{mouse::MouseMotion, InputSystem},
+    prelude::*,
+    time::Stopwatch,
+};
+
+use crate::{AppState, GameState};
+
+/// How long the main menu waits for input before starting a demo.
+const IDLE_SECS: f32 = 20.0;
+/// A demo goes back to the title after this long even if nobody died.
+const DEMO_SECS: f32 = 60.0;
+
+// RESOURCES -------------------------------------
+
+/// Present while the current run is a demo played by a `Seeker` bot. The run
+/// is not recorded, does not reach the high score table and ends on any
+/// button.
+#[derive(Resource)]
+pub struct AttractMode {
+    timer: Timer,
+}
+
+// INPUT -------------------------------------
+
+/// Every device someone could be holding.
+#[derive(SystemParam)]
+struct Devices<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    mouse_motion: EventReader<'w, 's, MouseMotion>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl Devices<'_, '_> {
+    fn any_just_pressed(&self) -> bool {
+        self.keys.get_just_pressed().next().is_some()
+            || self.mouse.get_just_pressed().next().is_some()
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.get_just_pressed().next().is_some())
+    }
+
+    // Anything held or moved, including a resting hand on a stick.
+    fn in_use(&mut self) -> bool {
+        let moved = self.mouse_motion.read().count() > 0;
+        moved
+            || self.keys.get_pressed().next().is_some()
+            || self.mouse.get_pressed().next().is_some()
+            || self.gamepads.iter().any(|gamepad| {
+                gamepad.get_pressed().next().is_some()
+                    || gamepad.left_stick().length() > 0.1
+                    || gamepad.right_stick().length() > 0.1
+            })
+    }
+}
+
+// SYSTEMS -------------------------------------
+
+fn start_demo_when_idle(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut devices: Devices,
+    mut idle: Local<Stopwatch>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if devices.in_use() {
+        idle.reset();
+        return;
+    }
+    if idle.tick(time.delta()).elapsed_secs() < IDLE_SECS {
+        return;
+    }
+    idle.reset();
+    commands.insert_resource(AttractMode {
+        timer: Timer::from_seconds(DEMO_SECS, TimerMode::Once),
+    });
+    next_state.set(AppState::InGame);
+}
+
+fn end_demo(
+    time: Res<Time>,
+    devices: Devices,
+    mut attract: ResMut<AttractMode>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if attract.timer.tick(time.delta()).finished() || devices.any_just_pressed() {
+        next_state.set(AppState::MainMenu);
+    }
+}
+
+fn end_demo_on_game_over(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::MainMenu);
+}
+
+fn clear_attract_mode(mut commands: Commands) {
+    commands.remove_resource::<AttractMode>();
+}
+
+// UI -------------------------------------
+
+// The title stays up while the demo plays behind it.
+fn spawn_demo_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                row_gap: Val::Px(20.0),
+                ..default()
+            },
+            StateScoped(AppState::InGame),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ImageNode {
+                    image: asset_server.load("title.png"),
+                    ..default()
+                },
+                Transform::from_scale(Vec3::splat(0.7)),
+            ));
+            parent.spawn((
+                Text::new("DEMO - press any button"),
+                TextFont {
+                    font_size: 28.,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        });
+}
+
+// PLUGIN -------------------------------------
+
+/// Plays a demo run behind the title when the main menu is left alone.
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            start_demo_when_idle.run_if(in_state(AppState::MainMenu)),
+        )
+        .add_systems(
+            OnEnter(AppState::InGame),
+            spawn_demo_overlay.run_if(resource_exists::<AttractMode>),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            end_demo_on_game_over.run_if(resource_exists::<AttractMode>),
+        )
+        .add_systems(OnExit(AppState::InGame), clear_attract_mode)
+        .add_systems(
+            PreUpdate,
+            end_demo
+                .run_if(in_state(AppState::InGame).and(resource_exists::<AttractMode>))
+                .after(InputSystem),
+        );
+    }
+}
@@ -1,19 +1,122 @@
 use bevy::prelude::*;
-use bevy_audio_controller::prelude::{AudioFiles, GlobalPlayEvent};
+use bevy_audio_controller::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const VOLUME_KEY: &str = "volume";
+
+// CHANNELS -------------------------------------
+
+#[derive(Component, Default, AudioChannel, Reflect)]
+pub struct MusicChannel;
+
+#[derive(Component, Default, AudioChannel, Reflect)]
+pub struct SfxChannel;
+
+/// How loud a channel plays relative to the sounds sent to it.
+trait ChannelVolume: Component {
+    fn level(volume: &AudioVolume) -> f32;
+}
+
+impl ChannelVolume for MusicChannel {
+    fn level(volume: &AudioVolume) -> f32 {
+        volume.master * volume.music
+    }
+}
+
+impl ChannelVolume for SfxChannel {
+    fn level(volume: &AudioVolume) -> f32 {
+        volume.master * volume.sfx
+    }
+}
+
+type NewSinks<C> = (Added<AudioSink>, With<C>);
+
+// RESOURCES -------------------------------------
+
+/// Volume levels from the settings screen, each from 0 to 1.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioVolume {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for AudioVolume {
+    fn default() -> Self {
+        AudioVolume {
+            master: 1.0,
+            music: 0.6,
+            sfx: 1.0,
+        }
+    }
+}
+
+impl AudioVolume {
+    pub fn save(&self) {
+        storage::save(VOLUME_KEY, self);
+    }
+}
+
+// COMPONENTS -------------------------------------
+
+/// Volume a sound was asked to play at, before the channel volume applies.
+#[derive(Component)]
+struct BaseVolume(f32);
 
 // SYSTEMS -------------------------------------
 
-fn setup_audio(mut sfx_play_ew: EventWriter<GlobalPlayEvent>) {
-    let event = GlobalPlayEvent::new(AudioFiles::Song18MP3).with_settings(PlaybackSettings::LOOP);
-    sfx_play_ew.send(event);
+fn setup_audio(mut music_play_ew: EventWriter<PlayEvent<MusicChannel>>) {
+    let event = PlayEvent::new(AudioFiles::Song18MP3).with_settings(PlaybackSettings::LOOP);
+    music_play_ew.send(event);
+}
+
+// Sinks only exist once a sound has started, so the channel volume is applied
+// here rather than when the play event is sent.
+fn scale_new_sinks<C: ChannelVolume>(
+    mut commands: Commands,
+    volume: Res<AudioVolume>,
+    sinks: Query<(Entity, &AudioSink), NewSinks<C>>,
+) {
+    for (entity, sink) in sinks.iter() {
+        commands.entity(entity).insert(BaseVolume(sink.volume()));
+        sink.set_volume(sink.volume() * C::level(&volume));
+    }
+}
+
+fn apply_volume<C: ChannelVolume>(
+    volume: Res<AudioVolume>,
+    sinks: Query<(&AudioSink, &BaseVolume), With<C>>,
+) {
+    for (sink, base) in sinks.iter() {
+        sink.set_volume(base.0 * C::level(&volume));
+    }
 }
 
 // PLUGIN -------------------------------------
 
-pub struct BackgroundAudioPlugin;
+/// Music and sound effects, each on their own channel so their volume can be
+/// changed while they play.
+pub struct GameAudioPlugin;
 
-impl Plugin for BackgroundAudioPlugin {
+impl Plugin for GameAudioPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_audio);
+        let volume = storage::load::<AudioVolume>(VOLUME_KEY).unwrap_or_default();
+        app.insert_resource(volume)
+            .register_audio_channel::<MusicChannel>()
+            .register_audio_channel::<SfxChannel>()
+            .add_systems(Startup, setup_audio)
+            .add_systems(
+                Last,
+                (
+                    (apply_volume::<MusicChannel>, apply_volume::<SfxChannel>)
+                        .run_if(resource_changed::<AudioVolume>),
+                    scale_new_sinks::<MusicChannel>,
+                    scale_new_sinks::<SfxChannel>,
+                )
+                    .chain(),
+            );
     }
 }
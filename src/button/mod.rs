@@ -0,0 +1,125 @@
+use bevy::{audio::PlaybackSettings, ecs::system::EntityCommands, prelude::*};
+use bevy_audio_controller::prelude::{AudioFiles, PlayEvent};
+
+use crate::audio::SfxChannel;
+
+pub const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+pub const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+pub const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+
+// SETS -------------------------------------
+
+/// Button feedback and action events run here. Systems reading the events
+/// should run after it to see a press in the same frame.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ButtonSet;
+
+// COMPONENTS -------------------------------------
+
+/// Sends a copy of the event when the button is pressed.
+///
+/// The event type has to be registered with
+/// [`ButtonActionAppExt::register_button_action`] first.
+#[derive(Component, Clone)]
+pub struct ButtonAction<E: Event + Clone>(pub E);
+
+// SYSTEMS -------------------------------------
+
+fn button_feedback(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut sfx_play_ew: EventWriter<PlayEvent<SfxChannel>>,
+) {
+    for (interaction, mut color, mut border_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                border_color.0 = Color::WHITE;
+                let event = PlayEvent::new(AudioFiles::ButtonClickOGG)
+                    .with_settings(PlaybackSettings::ONCE);
+                sfx_play_ew.send(event);
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+                border_color.0 = Color::WHITE;
+                let event =
+                    PlayEvent::new(AudioFiles::RolloverOGG).with_settings(PlaybackSettings::ONCE);
+                sfx_play_ew.send(event);
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+                border_color.0 = Color::BLACK;
+            }
+        }
+    }
+}
+
+fn send_button_action<E: Event + Clone>(
+    buttons: Query<(&Interaction, &ButtonAction<E>), Changed<Interaction>>,
+    mut events: EventWriter<E>,
+) {
+    for (interaction, action) in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            events.send(action.0.clone());
+        }
+    }
+}
+
+// SPAWNING -------------------------------------
+
+/// Spawns a standard menu button under `parent` that sends `action` when pressed.
+pub fn spawn_button<'a, E: Event + Clone>(
+    parent: &'a mut ChildBuilder,
+    label: impl Into<String>,
+    action: E,
+) -> EntityCommands<'a> {
+    let mut button = parent.spawn((
+        Button,
+        Node {
+            width: Val::Auto,
+            height: Val::Auto,
+            padding: UiRect::all(Val::Px(10.)),
+            border: UiRect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BorderColor(Color::BLACK),
+        BorderRadius::MAX,
+        BackgroundColor(NORMAL_BUTTON),
+        ButtonAction(action),
+    ));
+    button.with_child((
+        Text::new(label),
+        TextFont {
+            font_size: 33.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+    ));
+    button
+}
+
+// PLUGIN -------------------------------------
+
+pub trait ButtonActionAppExt {
+    /// Lets buttons carrying `ButtonAction<E>` send `E`.
+    fn register_button_action<E: Event + Clone>(&mut self) -> &mut Self;
+}
+
+impl ButtonActionAppExt for App {
+    fn register_button_action<E: Event + Clone>(&mut self) -> &mut Self {
+        self.add_event::<E>()
+            .add_systems(Update, send_button_action::<E>.in_set(ButtonSet))
+    }
+}
+
+pub struct ButtonPlugin;
+
+impl Plugin for ButtonPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, button_feedback.in_set(ButtonSet));
+    }
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::rng::RngSeed;
+
+/// Options passed on the command line.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct CliArgs {
+    /// Run the gameplay plugins without a window (`--headless`).
+    pub headless: bool,
+    /// Seed for every random decision in a run (`--seed <u64>`).
+    pub seed: Option<u64>,
+    /// Write the input of every run to this file (`--record <path>`).
+    pub record: Option<PathBuf>,
+    /// Play a recorded run back instead of reading devices (`--replay <path>`).
+    pub replay: Option<PathBuf>,
+    /// Name to put in the high score table (`--name <name>`).
+    pub name: Option<String>,
+    /// Let a bot play every run instead of the devices (`--bot`).
+    pub bot: bool,
+    /// Number of co-op players, 1 to 4 (`--players <n>`).
+    pub players: Option<usize>,
+    /// Co-op players share one pool of lives (`--shared-lives`).
+    pub shared_lives: bool,
+}
+
+impl CliArgs {
+    /// Reads `std::env::args`. This runs before logging is set up, so bad
+    /// arguments are reported on stderr and otherwise ignored.
+    pub fn parse() -> Self {
+        let mut cli = CliArgs::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => cli.headless = true,
+                "--bot" => cli.bot = true,
+                "--shared-lives" => cli.shared_lives = true,
+                "--players" => match args.next().map(|value| value.parse::<usize>()) {
+                    Some(Ok(players)) => cli.players = Some(players),
+                    _ => eprintln!("--players expects a number from 1 to 4"),
+                },
+                "--seed" => match args.next().map(|value| value.parse::<u64>()) {
+                    Some(Ok(seed)) => cli.seed = Some(seed),
+                    _ => eprintln!("--seed expects an unsigned integer"),
+                },
+                "--record" => match args.next() {
+                    Some(path) => cli.record = Some(path.into()),
+                    None => eprintln!("--record expects a file path"),
+                },
+                "--replay" => match args.next() {
+                    Some(path) => cli.replay = Some(path.into()),
+                    None => eprintln!("--replay expects a file path"),
+                },
+                "--name" => match args.next() {
+                    Some(name) => cli.name = Some(name),
+                    None => eprintln!("--name expects a name"),
+                },
+                unknown => eprintln!("Ignoring unknown argument {}", unknown),
+            }
+        }
+        cli
+    }
+
+    /// The seed given with `--seed`, falling back to `RngSeed::default`.
+    pub fn rng_seed(&self) -> RngSeed {
+        self.seed.map(RngSeed).unwrap_or_default()
+    }
+}
@@ -0,0 +1,252 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attract::AttractMode,
+    cli::CliArgs,
+    coop::CoopSettings,
+    holes::Hole,
+    input::{ActionState, DeviceActions, LatchedFire},
+    player::{Out, Player},
+    star::Star,
+    GameState,
+};
+
+/// Holes closer than their radius plus this push the seeker away.
+const AVOID_DISTANCE: f32 = 150.0;
+/// How far away the seeker starts shooting at a hole.
+const FIRE_RANGE: f32 = 400.0;
+/// Fixed ticks between the seeker's shots, so it doesn't empty its ammo at once.
+const FIRE_TICKS: u32 = 30;
+
+// SETS -------------------------------------
+
+/// Fills in every player's `Intent` in `FixedPreUpdate`. Replay playback
+/// writes `ReplayedIntents` before it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ControllerSet;
+
+// COMPONENTS -------------------------------------
+
+/// What a player does this tick. Gameplay reads this, never the devices.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Intent {
+    /// Desired direction, at most unit length.
+    pub movement: Vec2,
+    /// Direction to fire in. Missiles go the way the player faces when unset.
+    pub aim: Option<Vec2>,
+    pub fire: bool,
+}
+
+impl Intent {
+    /// Steering from `actions`. Firing comes from `LatchedFire` instead, so a
+    /// press is neither lost nor repeated by the fixed tick rate.
+    fn from_actions(actions: &ActionState, fire: bool) -> Self {
+        Intent {
+            movement: actions.movement,
+            aim: actions.aim,
+            fire,
+        }
+    }
+}
+
+/// Where a player's `Intent` comes from.
+#[derive(Component, Default)]
+pub enum Controller {
+    /// The keyboard and every gamepad together, for a single player.
+    #[default]
+    Input,
+    Keyboard,
+    /// One gamepad, by its entity.
+    Gamepad(Entity),
+    /// The player's frames of the replay being played back.
+    Replay,
+    /// A policy playing on its own.
+    Bot(Box<dyn Policy>),
+}
+
+impl Controller {
+    pub fn bot(policy: impl Policy) -> Self {
+        Controller::Bot(Box::new(policy))
+    }
+}
+
+// POLICIES -------------------------------------
+
+/// What a bot gets to see of the world each tick.
+pub struct Observation<'a> {
+    pub position: Vec2,
+    pub missiles: f32,
+    pub stars: &'a [Vec2],
+    /// Position and radius of every hole.
+    pub holes: &'a [(Vec2, f32)],
+}
+
+/// Decides a bot's `Intent` from what it sees. Called once per fixed tick,
+/// so a policy that only looks at the world stays deterministic and replays
+/// the same way.
+pub trait Policy: Send + Sync + 'static {
+    fn act(&mut self, observation: &Observation) -> Intent;
+}
+
+/// Heads for the nearest star, veers away from holes that get close and
+/// shoots at the nearest hole in range.
+#[derive(Default)]
+pub struct Seeker {
+    cooldown: u32,
+}
+
+impl Policy for Seeker {
+    fn act(&mut self, observation: &Observation) -> Intent {
+        let position = observation.position;
+        let mut movement = observation
+            .stars
+            .iter()
+            .map(|star| *star - position)
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+            .map(Vec2::normalize_or_zero)
+            .unwrap_or(Vec2::ZERO);
+
+        let mut target: Option<Vec2> = None;
+        for &(hole, radius) in observation.holes {
+            let offset = position - hole;
+            let distance = offset.length();
+            let danger = radius + AVOID_DISTANCE;
+            if distance < danger {
+                movement += offset.normalize_or_zero() * 3.0 * (1.0 - distance / danger);
+            }
+            if distance < FIRE_RANGE && target.is_none_or(|target| distance < target.length()) {
+                target = Some(-offset);
+            }
+        }
+
+        self.cooldown = self.cooldown.saturating_sub(1);
+        let fire = target.is_some() && self.cooldown == 0 && observation.missiles >= 1.0;
+        if fire {
+            self.cooldown = FIRE_TICKS;
+        }
+
+        Intent {
+            movement: movement.clamp_length_max(1.0),
+            aim: target.map(Vec2::normalize_or_zero),
+            fire,
+        }
+    }
+}
+
+// SPAWNING -------------------------------------
+
+/// Picks who plays a new run: a bot for `--bot` and demos, the replay while
+/// one is played back, and the devices otherwise.
+#[derive(SystemParam)]
+pub struct RunController<'w, 's> {
+    cli: Option<Res<'w, CliArgs>>,
+    attract: Option<Res<'w, AttractMode>>,
+    replayed: Option<Res<'w, ReplayedIntents>>,
+    coop: Res<'w, CoopSettings>,
+    gamepads: Query<'w, 's, Entity, With<Gamepad>>,
+}
+
+impl RunController<'_, '_> {
+    /// A controller for every player in the run, player one first. A single
+    /// player gets every device. In co-op player one has the keyboard and
+    /// the others a gamepad each, so there are only as many players as
+    /// there are devices to go round.
+    pub fn controllers(&self) -> Vec<Controller> {
+        let players = self.coop.players;
+        let bot = self.cli.as_ref().is_some_and(|cli| cli.bot);
+        if bot || self.attract.is_some() {
+            return (0..players)
+                .map(|_| Controller::bot(Seeker::default()))
+                .collect();
+        }
+        if self.replayed.is_some() {
+            return (0..players).map(|_| Controller::Replay).collect();
+        }
+        if players == 1 {
+            return vec![Controller::Input];
+        }
+
+        let mut gamepads: Vec<Entity> = self.gamepads.iter().collect();
+        gamepads.sort();
+        if gamepads.len() + 1 < players {
+            warn!(
+                "{} players need {} gamepads but only {} are connected",
+                players,
+                players - 1,
+                gamepads.len()
+            );
+        }
+        std::iter::once(Controller::Keyboard)
+            .chain(gamepads.into_iter().map(Controller::Gamepad))
+            .take(players)
+            .collect()
+    }
+}
+
+// RESOURCES -------------------------------------
+
+/// Every player's `Intent` for this tick, in player order, while a replay
+/// is played back.
+#[derive(Resource, Default)]
+pub struct ReplayedIntents(pub Vec<Intent>);
+
+// SYSTEMS -------------------------------------
+
+fn drive_players(
+    actions: Res<ActionState>,
+    devices: Res<DeviceActions>,
+    latched: Res<LatchedFire>,
+    replayed: Option<Res<ReplayedIntents>>,
+    stars: Query<&Transform, With<Star>>,
+    holes: Query<(&Transform, &Hole)>,
+    mut players: Query<(&Transform, &Player, &mut Controller, &mut Intent), Without<Out>>,
+) {
+    let star_positions: Vec<Vec2> = stars
+        .iter()
+        .map(|star| star.translation.truncate())
+        .collect();
+    let hole_positions: Vec<(Vec2, f32)> = holes
+        .iter()
+        .map(|(transform, hole)| (transform.translation.truncate(), hole.radius))
+        .collect();
+
+    for (transform, player, mut controller, mut intent) in players.iter_mut() {
+        *intent = match &mut *controller {
+            Controller::Input => Intent::from_actions(&actions, latched.any),
+            Controller::Keyboard => Intent::from_actions(&devices.keyboard, latched.keyboard),
+            // A gamepad that went away leaves its player standing still.
+            Controller::Gamepad(gamepad) => devices
+                .gamepads
+                .get(gamepad)
+                .map(|actions| Intent::from_actions(actions, latched.gamepads.contains(gamepad)))
+                .unwrap_or_default(),
+            Controller::Replay => replayed
+                .as_ref()
+                .and_then(|replayed| replayed.0.get(player.slot).copied())
+                .unwrap_or_default(),
+            Controller::Bot(policy) => policy.act(&Observation {
+                position: transform.translation.truncate(),
+                missiles: player.missiles,
+                stars: &star_positions,
+                holes: &hole_positions,
+            }),
+        };
+    }
+}
+
+// PLUGIN -------------------------------------
+
+/// Turns each player's `Controller` into its `Intent` for the tick.
+pub struct ControllerPlugin;
+
+impl Plugin for ControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedPreUpdate,
+            drive_players
+                .in_set(ControllerSet)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{cli::CliArgs, storage};
+
+const COOP_KEY: &str = "coop";
+
+pub const MAX_PLAYERS: usize = 4;
+
+// RESOURCES -------------------------------------
+
+/// How many play the next run and how they share lives, picked on the title
+/// screen. With more than one player, player one has the keyboard and each
+/// of the others a gamepad of their own.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoopSettings {
+    pub players: usize,
+    /// Every player draws from one pool of lives instead of their own, and
+    /// everyone is out together when it runs dry.
+    pub shared_lives: bool,
+}
+
+impl Default for CoopSettings {
+    fn default() -> Self {
+        CoopSettings {
+            players: 1,
+            shared_lives: false,
+        }
+    }
+}
+
+impl CoopSettings {
+    pub fn save(&self) {
+        storage::save(COOP_KEY, self);
+    }
+
+    /// One more player, back to one after `MAX_PLAYERS`.
+    pub fn cycle_players(&mut self) {
+        self.players = self.players % MAX_PLAYERS + 1;
+    }
+}
+
+// PLUGIN -------------------------------------
+
+/// Loads the co-op setup. `--players` and `--shared-lives` override what was
+/// saved for this session. Headless runs ignore the saved setup so they play
+/// the same on every machine.
+pub struct CoopPlugin;
+
+impl Plugin for CoopPlugin {
+    fn build(&self, app: &mut App) {
+        let cli = app
+            .world()
+            .get_resource::<CliArgs>()
+            .cloned()
+            .unwrap_or_default();
+        let mut settings = if cli.headless {
+            CoopSettings::default()
+        } else {
+            storage::load::<CoopSettings>(COOP_KEY).unwrap_or_default()
+        };
+        if let Some(players) = cli.players {
+            settings.players = players;
+        }
+        settings.shared_lives |= cli.shared_lives;
+        settings.players = settings.players.clamp(1, MAX_PLAYERS);
+        app.insert_resource(settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_players_wraps_after_the_last() {
+        let mut settings = CoopSettings::default();
+        let mut seen = vec![settings.players];
+        for _ in 0..MAX_PLAYERS {
+            settings.cycle_players();
+            seen.push(settings.players);
+        }
+        assert_eq!(seen, [1, 2, 3, 4, 1]);
+    }
+}
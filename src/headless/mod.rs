@@ -0,0 +1,211 @@
+use std::time::Duration;
+
+use bevy::{
+    app::AppExit,
+    asset::AssetMetaCheck,
+    image::{CompressedImageFormats, ImageLoader},
+    input::InputPlugin,
+    log::LogPlugin,
+    prelude::*,
+    scene::ScenePlugin,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+use bevy_audio_controller::prelude::PlayEvent;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    arena::ArenaPlugin,
+    audio::{MusicChannel, SfxChannel},
+    controller::ControllerPlugin,
+    coop::CoopPlugin,
+    holes::HolePlugin,
+    input::PlayerInputPlugin,
+    player::Player,
+    player::PlayerPlugin,
+    powerup::PowerUpPlugin,
+    replay::ReplayPlugin,
+    rng::RngPlugin,
+    score::ScorePlugin,
+    setup_game, spawn_arena,
+    star::StarPlugin,
+    tuning::tuning_ready,
+    tuning::TuningPlugin,
+    wave::WavePlugin,
+    AppState, GameState,
+};
+
+/// Length of one simulated frame. Time advances by exactly this much per
+/// update, so a headless run does not depend on how fast the host is.
+const FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+// SYSTEMS -------------------------------------
+
+// There is no menu to go through, the run starts as soon as the tuning is in.
+fn start_run(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::InGame);
+}
+
+fn exit_on_game_over(players: Query<&Player>, mut exit: EventWriter<AppExit>) {
+    for player in players.iter() {
+        info!(
+            "Game over. Player {} stars: {}",
+            player.slot + 1,
+            player.stars
+        );
+    }
+    exit.send(AppExit::Success);
+}
+
+// PLUGIN -------------------------------------
+
+/// Runs the gameplay plugins without a window, renderer or audio output.
+///
+/// Sprites and meshes are still spawned as plain components so the
+/// gameplay systems see the same world they do in the windowed build.
+/// The app exits when the run reaches `GameState::GameOver`.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins,
+            LogPlugin::default(),
+            StatesPlugin,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin {
+                meta_check: AssetMetaCheck::Never,
+                ..AssetPlugin::default()
+            },
+            ScenePlugin,
+            InputPlugin,
+        ))
+        .init_asset::<Image>()
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .register_asset_loader(ImageLoader::new(CompressedImageFormats::NONE))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+        // Nothing plays these, the events only have to exist for the systems that send them.
+        .add_event::<PlayEvent<MusicChannel>>()
+        .add_event::<PlayEvent<SfxChannel>>()
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0).in_fixed_schedule())
+        .add_plugins((
+            ArenaPlugin,
+            TuningPlugin,
+            RngPlugin,
+            PlayerInputPlugin,
+            CoopPlugin,
+            ReplayPlugin,
+            ControllerPlugin,
+        ))
+        .add_plugins((
+            PlayerPlugin,
+            StarPlugin,
+            HolePlugin,
+            WavePlugin,
+            PowerUpPlugin,
+            ScorePlugin,
+        ))
+        .init_state::<AppState>()
+        .add_sub_state::<GameState>()
+        .enable_state_scoped_entities::<AppState>()
+        .add_systems(
+            Update,
+            start_run.run_if(in_state(AppState::Loading).and(tuning_ready)),
+        )
+        .add_systems(OnEnter(AppState::InGame), spawn_arena)
+        .add_systems(OnEnter(GameState::Start), setup_game)
+        .add_systems(OnEnter(GameState::GameOver), exit_on_game_over);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::query::QueryFilter;
+
+    use super::*;
+    use crate::{cli::CliArgs, holes::Hole, rng::RngSeed, score::Score, star::Star};
+
+    /// Frames to wait for the tuning asset before giving up.
+    const LOADING_FRAMES: usize = 1000;
+    /// Ten seconds of play, long enough for a few holes and stars to spawn.
+    const RUN_FRAMES: usize = 600;
+
+    #[derive(Debug, PartialEq)]
+    struct Snapshot {
+        players: Vec<Vec2>,
+        holes: Vec<Vec2>,
+        stars: Vec<Vec2>,
+        score: u32,
+    }
+
+    fn run(seed: u64) -> App {
+        let mut app = App::new();
+        app.insert_resource(RngSeed(seed))
+            .insert_resource(CliArgs::default())
+            .add_plugins(HeadlessPlugin)
+            // One fixed tick per update, so two runs line up however long the
+            // tuning took to load.
+            .insert_resource(Time::<Fixed>::from_duration(FRAME_TIME));
+
+        for _ in 0..LOADING_FRAMES {
+            app.update();
+            if *app.world().resource::<State<AppState>>() == AppState::InGame {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(
+            *app.world().resource::<State<AppState>>(),
+            AppState::InGame,
+            "the tuning never loaded"
+        );
+
+        for _ in 0..RUN_FRAMES {
+            app.update();
+        }
+        app
+    }
+
+    fn positions<F: QueryFilter>(world: &mut World) -> Vec<Vec2> {
+        let mut positions: Vec<Vec2> = world
+            .query_filtered::<&Transform, F>()
+            .iter(world)
+            .map(|transform| transform.translation.truncate())
+            .collect();
+        positions.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        positions
+    }
+
+    fn snapshot(app: &mut App) -> Snapshot {
+        let world = app.world_mut();
+        let mut players: Vec<(usize, Vec2)> = world
+            .query::<(&Player, &Transform)>()
+            .iter(world)
+            .map(|(player, transform)| (player.slot, transform.translation.truncate()))
+            .collect();
+        players.sort_by_key(|(slot, _)| *slot);
+        Snapshot {
+            players: players.into_iter().map(|(_, position)| position).collect(),
+            holes: positions::<With<Hole>>(world),
+            stars: positions::<With<Star>>(world),
+            score: world.resource::<Score>().points,
+        }
+    }
+
+    #[test]
+    fn run_spawns_players_holes_and_stars() {
+        let snapshot = snapshot(&mut run(42));
+        assert!(!snapshot.players.is_empty());
+        assert!(!snapshot.holes.is_empty());
+        assert!(!snapshot.stars.is_empty());
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_run() {
+        let first = snapshot(&mut run(7));
+        let second = snapshot(&mut run(7));
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,291 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{attract::AttractMode, cli::CliArgs, player::Player, score::Score, storage, GameState};
+
+const HIGH_SCORES_KEY: &str = "highscores";
+/// How many runs the table keeps.
+const MAX_HIGH_SCORES: usize = 10;
+
+const TABLE_TEXT: Color = Color::srgb(0.9, 0.9, 0.9);
+const TABLE_HIGHLIGHT: Color = Color::srgb(1.0, 0.85, 0.2);
+
+// HIGH SCORES -------------------------------------
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    /// What the table is ranked by.
+    pub score: u32,
+    pub stars: u32,
+    /// Time spent in `GameState::Playing`, pauses excluded.
+    pub seconds: f32,
+    /// `YYYY-MM-DD`, in UTC.
+    pub date: String,
+}
+
+/// Best runs first, at most `MAX_HIGH_SCORES` of them.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HighScores(pub Vec<HighScore>);
+
+impl HighScores {
+    /// Adds `entry` in rank order and returns its rank, or `None` if it did
+    /// not make the table. Ties go to the older run.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let rank = self
+            .0
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.0.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.0.insert(rank, entry);
+        self.0.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+// RESOURCES -------------------------------------
+
+/// Name stored with each run: `--name`, else the login name.
+#[derive(Resource, Clone, Debug)]
+pub struct PlayerName(pub String);
+
+impl PlayerName {
+    fn from_cli(cli: &CliArgs) -> Self {
+        let name = cli
+            .name
+            .clone()
+            .or_else(|| std::env::var("USER").ok())
+            .or_else(|| std::env::var("USERNAME").ok())
+            .unwrap_or_else(|| "Player".to_string());
+        PlayerName(name)
+    }
+}
+
+/// How long the current run has been played for.
+#[derive(Resource, Default)]
+pub struct RunTime {
+    pub seconds: f32,
+}
+
+/// Rank the last finished run got in the table, if any.
+#[derive(Resource, Default)]
+pub struct LastRunRank(pub Option<usize>);
+
+// SYSTEMS -------------------------------------
+
+fn reset_run_time(mut run_time: ResMut<RunTime>) {
+    run_time.seconds = 0.0;
+}
+
+fn tick_run_time(time: Res<Time>, mut run_time: ResMut<RunTime>) {
+    run_time.seconds += time.delta_secs();
+}
+
+pub fn record_run(
+    players: Query<&Player>,
+    score: Res<Score>,
+    run_time: Res<RunTime>,
+    name: Res<PlayerName>,
+    mut scores: ResMut<HighScores>,
+    mut last_rank: ResMut<LastRunRank>,
+) {
+    let stars = players.iter().map(|player| player.stars).sum();
+    last_rank.0 = scores.insert(HighScore {
+        name: name.0.clone(),
+        score: score.points,
+        stars,
+        seconds: run_time.seconds,
+        date: today(),
+    });
+    if last_rank.0.is_some() {
+        storage::save(HIGH_SCORES_KEY, &*scores);
+    }
+}
+
+// UI -------------------------------------
+
+/// Spawns the high score table under `parent`, with row `highlight` picked out.
+pub fn spawn_high_score_table(
+    parent: &mut ChildBuilder,
+    scores: &HighScores,
+    highlight: Option<usize>,
+) {
+    parent
+        .spawn((
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+            BorderRadius::all(Val::Px(8.0)),
+        ))
+        .with_children(|panel| {
+            panel.spawn(table_text("High Scores", 24.0, TABLE_TEXT));
+
+            if scores.0.is_empty() {
+                panel.spawn(table_text("No runs yet", 18.0, TABLE_TEXT));
+                return;
+            }
+
+            panel
+                .spawn(Node {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::auto(6),
+                    column_gap: Val::Px(16.0),
+                    row_gap: Val::Px(2.0),
+                    ..default()
+                })
+                .with_children(|grid| {
+                    for header in ["#", "Name", "Score", "Stars", "Time", "Date"] {
+                        grid.spawn(table_text(header, 18.0, TABLE_TEXT));
+                    }
+                    for (rank, entry) in scores.0.iter().enumerate() {
+                        let color = if highlight == Some(rank) {
+                            TABLE_HIGHLIGHT
+                        } else {
+                            TABLE_TEXT
+                        };
+                        let seconds = entry.seconds as u32;
+                        let cells = [
+                            (rank + 1).to_string(),
+                            entry.name.clone(),
+                            entry.score.to_string(),
+                            entry.stars.to_string(),
+                            format!("{}:{:02}", seconds / 60, seconds % 60),
+                            entry.date.clone(),
+                        ];
+                        for cell in cells {
+                            grid.spawn(table_text(cell, 18.0, color));
+                        }
+                    }
+                });
+        });
+}
+
+fn table_text(text: impl Into<String>, font_size: f32, color: Color) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size,
+            ..default()
+        },
+        TextColor(color),
+    )
+}
+
+// DATE -------------------------------------
+
+#[cfg(not(target_arch = "wasm32"))]
+fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    // Days since 1970-01-01 to a civil date, after Howard Hinnant's
+    // `civil_from_days`.
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(target_arch = "wasm32")]
+fn today() -> String {
+    let iso: String = js_sys::Date::new_0().to_iso_string().into();
+    iso.chars().take(10).collect()
+}
+
+// PLUGIN -------------------------------------
+
+/// Keeps the best runs between sessions. Replays, bot runs and demos are not
+/// recorded.
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        let cli = app
+            .world()
+            .get_resource::<CliArgs>()
+            .cloned()
+            .unwrap_or_default();
+        let scores = storage::load::<HighScores>(HIGH_SCORES_KEY).unwrap_or_default();
+
+        app.insert_resource(scores)
+            .insert_resource(PlayerName::from_cli(&cli))
+            .init_resource::<RunTime>()
+            .init_resource::<LastRunRank>()
+            .add_systems(OnEnter(GameState::Start), reset_run_time)
+            .add_systems(
+                FixedUpdate,
+                tick_run_time.run_if(in_state(GameState::Playing)),
+            );
+        if cli.replay.is_none() && !cli.bot {
+            app.add_systems(
+                OnEnter(GameState::GameOver),
+                record_run.run_if(not(resource_exists::<AttractMode>)),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            stars: 0,
+            seconds: 0.0,
+            date: "2025-01-01".to_string(),
+        }
+    }
+
+    fn names(scores: &HighScores) -> Vec<&str> {
+        scores.0.iter().map(|score| score.name.as_str()).collect()
+    }
+
+    #[test]
+    fn insert_keeps_rank_order() {
+        let mut scores = HighScores::default();
+        assert_eq!(scores.insert(entry("b", 20)), Some(0));
+        assert_eq!(scores.insert(entry("a", 30)), Some(0));
+        assert_eq!(scores.insert(entry("c", 10)), Some(2));
+        assert_eq!(names(&scores), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn ties_go_to_the_older_run() {
+        let mut scores = HighScores::default();
+        scores.insert(entry("old", 20));
+        assert_eq!(scores.insert(entry("new", 20)), Some(1));
+        assert_eq!(names(&scores), ["old", "new"]);
+    }
+
+    #[test]
+    fn insert_keeps_at_most_ten() {
+        let mut scores = HighScores::default();
+        for score in 1..=MAX_HIGH_SCORES as u32 {
+            scores.insert(entry("full", score * 10));
+        }
+        assert_eq!(scores.insert(entry("low", 5)), None);
+        assert_eq!(scores.insert(entry("top", 1000)), Some(0));
+        assert_eq!(scores.0.len(), MAX_HIGH_SCORES);
+        assert_eq!(scores.0[0].name, "top");
+        assert_eq!(scores.0.last().unwrap().score, 20);
+    }
+}
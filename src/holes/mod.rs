@@ -1,71 +1,164 @@
+use std::time::Duration;
+
 use bevy::{
     audio::{PlaybackMode, Volume},
     prelude::*,
 };
-use bevy_audio_controller::prelude::{AudioFiles, GlobalPlayEvent};
+use bevy_audio_controller::prelude::{AudioFiles, PlayEvent};
 use bevy_rapier2d::prelude::*;
+use rand::{distributions::WeightedIndex, Rng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    player::{Missile, Player},
+    arena::{started_with, ArenaBounds, HOLE_GROUP, MISSILE_GROUP, PLAYER_GROUP, STAR_GROUP},
+    audio::SfxChannel,
+    player::{Missile, Out, Player},
+    rng::{GameRng, RngSet},
     star::Star,
-    GameState,
+    tuning::{HoleTuning, Tuning},
+    wave::Wave,
+    AppState, GameState,
 };
 
+/// Radius of a normal hole. Other kinds scale it.
+const HOLE_RADIUS: f32 = 40.0;
+
+// Events ---------------------------------------------------------------------
+
+/// A missile destroyed a hole.
+#[derive(Event)]
+pub struct HoleDestroyed {
+    pub position: Vec2,
+    /// The player whose missile finished it off.
+    pub shooter: Entity,
+}
+
+/// Merged holes reached `HoleTuning::max_merge_mass` and collapsed into a
+/// gravity well.
+#[derive(Event)]
+pub struct HoleCollapsed {
+    pub hole: Entity,
+}
+
 // Components -----------------------------------------------------------------
 #[derive(Component)]
 pub struct Hole {
     pub speed: f32,
+    pub radius: f32,
+    /// A normal hole is 1. Merging adds the masses, and the size grows with
+    /// the square root.
+    pub mass: f32,
+    /// Missile hits left.
+    pub hit_points: u32,
 }
 
-impl Default for Hole {
-    fn default() -> Self {
-        Self { speed: 200.0 }
+/// Hole variants unlocked by later waves. Their speed, size and first wave
+/// come from `WaveTuning::kinds`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HoleKind {
+    Normal,
+    Fast,
+    Big,
+    /// Pulls the player and missiles towards it.
+    Well,
+}
+
+impl HoleKind {
+    fn color(self) -> Color {
+        match self {
+            HoleKind::Normal => Color::WHITE,
+            HoleKind::Fast => Color::srgb(1.0, 0.6, 0.6),
+            HoleKind::Big => Color::srgb(0.7, 0.6, 1.0),
+            HoleKind::Well => Color::srgb(0.5, 0.9, 1.0),
+        }
     }
 }
 
+/// Hole that pulls bodies with an `ExternalForce` towards it.
+#[derive(Component)]
+pub struct GravityWell;
+
 #[derive(Component)]
 struct HoleDirection;
 
+// Resources ------------------------------------------------------------------
+#[derive(Resource)]
+struct HoleSpawnTimer(Timer);
+
+impl Default for HoleSpawnTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            HoleTuning::default().spawn_secs,
+            TimerMode::Once,
+        ))
+    }
+}
+
 // Systems --------------------------------------------------------------------
-fn cleanup_holes(mut commands: Commands, holes: Query<Entity, With<Hole>>) {
+fn cleanup_holes(
+    mut commands: Commands,
+    holes: Query<Entity, With<Hole>>,
+    mut timer: ResMut<HoleSpawnTimer>,
+) {
     for hole in holes.iter() {
         commands.entity(hole).despawn_recursive();
     }
+    timer.0.reset();
 }
 
+fn tick_hole_spawn_timer(
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    wave: Res<Wave>,
+    mut timer: ResMut<HoleSpawnTimer>,
+) {
+    if tuning.is_changed() || wave.is_changed() {
+        timer
+            .0
+            .set_duration(Duration::from_secs_f32(tuning.hole_spawn_secs(wave.number)));
+    }
+    timer.0.tick(time.delta());
+}
+
+#[allow(clippy::too_many_arguments)]
 fn spawn_hole(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    time: Res<Time>,
-    mut timer: Local<f32>,
-    // Query for any entity with both Transform and Collider components.
-    colliders: Query<&Transform, With<Hole>>,
-    windows: Query<&Window>,
-    query: Query<Entity, With<Hole>>,
+    tuning: Res<Tuning>,
+    wave: Res<Wave>,
+    mut timer: ResMut<HoleSpawnTimer>,
+    mut rng: ResMut<GameRng>,
+    holes: Query<(&Transform, &Hole)>,
+    bounds: Res<ArenaBounds>,
 ) {
-    *timer += time.delta_secs();
-    // Spawn a new hole every 3 seconds if there are less than 4 stars.
-    let current_holes = query.iter().count();
-    if *timer >= 3.0 && current_holes <= 8 {
-        *timer = 0.0;
-        let window = windows.single();
+    // Spawn a new hole every few seconds until the cap is reached.
+    let current_holes = holes.iter().count();
+    if wave.spawning() && timer.0.finished() && current_holes < tuning.max_holes(wave.number) {
+        timer.0.reset();
         let model = asset_server.load("hole_large_end.png");
 
-        const HOLE_RADIUS: f32 = 40.0;
         const MAX_ATTEMPTS: usize = 10;
 
-        // Closure to generate a random candidate position within window bounds,
-        // ensuring the entire 32x32 hole is visible and avoiding the top 56 pixels.
-        let generate_candidate = || -> Vec2 {
-            let half_hole = HOLE_RADIUS / 2.;
+        // Pick a kind unlocked by this wave, weighted by how common it is.
+        let kinds: Vec<_> = tuning.hole_kinds(wave.number).collect();
+        let Ok(weights) = WeightedIndex::new(kinds.iter().map(|kind| kind.weight)) else {
+            return;
+        };
+        let kind = kinds[rng.sample(&weights)];
+        let radius = HOLE_RADIUS * kind.scale;
+
+        // Closure to generate a random candidate position within arena bounds,
+        // ensuring the entire hole is visible and avoiding the top 56 pixels.
+        let mut generate_candidate = || -> Vec2 {
+            let half_hole = radius / 2.;
             let margin_top = 56.0;
-            let x_min = -window.width() / 2.0 + half_hole;
-            let x_max = window.width() / 2.0 - half_hole;
+            let x_min = -bounds.half_width() + half_hole;
+            let x_max = bounds.half_width() - half_hole;
             // Ensure the hole is fully visible and not in the top 56 pixels:
-            let y_min = -window.height() / 2.0 + half_hole;
-            let y_max = window.height() / 2.0 - half_hole - margin_top;
-            let x = rand::random::<f32>() * (x_max - x_min) + x_min;
-            let y = rand::random::<f32>() * (y_max - y_min) + y_min;
+            let y_min = -bounds.half_height() + half_hole;
+            let y_max = bounds.half_height() - half_hole - margin_top;
+            let x = rng.gen::<f32>() * (x_max - x_min) + x_min;
+            let y = rng.gen::<f32>() * (y_max - y_min) + y_min;
             Vec2::new(x, y)
         };
 
@@ -74,14 +167,9 @@ fn spawn_hole(
         let mut valid_spawn = false;
         for _ in 0..MAX_ATTEMPTS {
             valid_spawn = true;
-            // Check collision against all entities with a Collider.
-            for transform in colliders.iter() {
-                // Here we assume that the collider is a circle (Collider::ball),
-                // so we perform a simple distance check. If your game uses different
-                // collider shapes, you'll need to adjust this collision test accordingly.
-
+            for (transform, other) in holes.iter() {
                 let other_position = transform.translation.truncate();
-                if other_position.distance(spawn_position) < HOLE_RADIUS * 2.0 {
+                if other_position.distance(spawn_position) < radius + other.radius {
                     valid_spawn = false;
                     break;
                 }
@@ -98,66 +186,125 @@ fn spawn_hole(
             return;
         }
 
-        commands.spawn((
-            Sprite::from_image(model),
-            Hole::default(),
-            RigidBody::Fixed,
+        let mut sprite = Sprite::from_image(model);
+        sprite.color = kind.kind.color();
+        let mut hole = commands.spawn((
+            sprite,
+            Hole {
+                speed: tuning.hole_speed(wave.number) * kind.speed_multiplier,
+                radius,
+                mass: kind.scale * kind.scale,
+                hit_points: 1,
+            },
+            // Moved by `move_holes`, so kinematic; it has to opt in to
+            // touching fixed stars and other holes.
+            RigidBody::KinematicPositionBased,
+            // Scaled with the sprite by the transform.
             Collider::ball(HOLE_RADIUS),
-            Transform::from_translation(Vec3::new(spawn_position.x, spawn_position.y, 1.0)),
-            HoleDirection,
             Sensor,
+            CollisionGroups::new(
+                HOLE_GROUP,
+                PLAYER_GROUP | MISSILE_GROUP | STAR_GROUP | HOLE_GROUP,
+            ),
+            ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_STATIC
+                | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+            ActiveEvents::COLLISION_EVENTS,
+            Transform::from_translation(Vec3::new(spawn_position.x, spawn_position.y, 1.0))
+                .with_scale(Vec3::splat(kind.scale)),
+            HoleDirection,
+            StateScoped(AppState::InGame),
         ));
+        if kind.kind == HoleKind::Well {
+            hole.insert(GravityWell);
+        }
     }
 }
 
 fn check_hole_star_collision(
     mut commands: Commands,
-    holes: Query<&Transform, With<Hole>>,
-    stars: Query<(Entity, &Transform), With<Star>>,
+    mut collisions: EventReader<CollisionEvent>,
+    holes: Query<(), With<Hole>>,
+    stars: Query<(), With<Star>>,
 ) {
-    for hole_transform in holes.iter() {
-        for (star_entity, star_transform) in stars.iter() {
-            let distance = hole_transform
-                .translation
-                .distance(star_transform.translation);
-            if distance < 42. {
-                // Adjust collision radius as needed
-                commands.entity(star_entity).despawn();
-            }
+    for collision in collisions.read() {
+        let Some((star, other)) = started_with(collision, |entity| stars.contains(entity)) else {
+            continue;
+        };
+        if holes.contains(other) {
+            commands.entity(star).despawn();
         }
     }
 }
-fn check_hole_collisions(mut commands: Commands, query: Query<(Entity, &Transform), With<Hole>>) {
-    let collision_distance = 80.0;
-    let mut to_despawn = std::collections::HashSet::new();
-
-    for [item_a, item_b] in query.iter_combinations() {
-        let (entity_a, transform_a) = item_a;
-        let (entity_b, transform_b) = item_b;
-        let distance = transform_a.translation.distance(transform_b.translation);
-        if distance < collision_distance {
-            to_despawn.insert(entity_a);
-            to_despawn.insert(entity_b);
+
+// The heavier hole swallows the other. The result is bigger, slower and
+// takes more hits, until it collapses into a gravity well.
+fn merge_holes(
+    mut commands: Commands,
+    tuning: Res<Tuning>,
+    mut collisions: EventReader<CollisionEvent>,
+    mut holes: Query<(&mut Hole, &mut Transform, Has<GravityWell>)>,
+    mut collapsed: EventWriter<HoleCollapsed>,
+) {
+    let mut swallowed = std::collections::HashSet::new();
+
+    for collision in collisions.read() {
+        let Some((hole_a, hole_b)) = started_with(collision, |entity| holes.contains(entity))
+        else {
+            continue;
+        };
+        if swallowed.contains(&hole_a) || swallowed.contains(&hole_b) {
+            continue;
         }
-    }
+        let mass_of = |entity| holes.get(entity).map_or(0.0, |(hole, ..)| hole.mass);
+        let (keeper, eaten) = if mass_of(hole_a) >= mass_of(hole_b) {
+            (hole_a, hole_b)
+        } else {
+            (hole_b, hole_a)
+        };
+        let Ok([(mut hole, mut transform, is_well), (other, other_transform, other_is_well)]) =
+            holes.get_many_mut([keeper, eaten])
+        else {
+            continue;
+        };
+
+        let max_mass = tuning.holes.max_merge_mass;
+        let was_full = hole.mass >= max_mass;
+        let mass = (hole.mass + other.mass).min(max_mass);
+        let weight = other.mass / (hole.mass + other.mass);
+        transform.translation = transform
+            .translation
+            .lerp(other_transform.translation, weight);
+        hole.speed = hole.speed.lerp(other.speed, weight) * tuning.holes.merge_speed_multiplier;
+        hole.hit_points += other.hit_points;
+        hole.mass = mass;
+        hole.radius = HOLE_RADIUS * mass.sqrt();
+        transform.scale = Vec3::splat(mass.sqrt());
 
-    for entity in to_despawn {
-        commands.entity(entity).despawn_recursive();
+        swallowed.insert(eaten);
+        commands.entity(eaten).despawn_recursive();
+        if other_is_well && !is_well {
+            commands.entity(keeper).try_insert(GravityWell);
+        }
+        if mass >= max_mass && !was_full {
+            commands.entity(keeper).try_insert(GravityWell);
+            collapsed.send(HoleCollapsed { hole: keeper });
+        }
     }
 }
 
 fn move_holes(
     time: Res<Time>,
-    windows: Query<&Window>,
+    bounds: Res<ArenaBounds>,
     stars: Query<&Transform, (With<Star>, Without<Hole>, Without<Player>)>,
     mut holes: Query<(&mut Transform, &Hole), With<Hole>>,
-    players: Query<(Entity, &mut Transform), (With<Player>, Without<Hole>, Without<Star>)>,
+    players: Query<
+        (Entity, &mut Transform),
+        (With<Player>, Without<Hole>, Without<Star>, Without<Out>),
+    >,
 ) {
-    let window = windows.single();
-    let half_width = window.width() / 2.0;
-    let half_height = window.height() / 2.0;
-    // The hole has a diameter of 80 (radius 40).
-    const HOLE_RADIUS: f32 = 40.0;
+    let half_width = bounds.half_width();
+    let half_height = bounds.half_height();
     // Prevent holes from moving into the top 50 pixels.
     const TOP_MARGIN: f32 = 50.0;
     // Speed at which holes move.
@@ -186,13 +333,11 @@ fn move_holes(
                 let target = transform.translation + displacement;
 
                 // Clamp target to ensure the entire hole remains within visible bounds.
-                let clamped_x = target
-                    .x
-                    .clamp(-half_width + HOLE_RADIUS, half_width - HOLE_RADIUS);
-                let clamped_y = target.y.clamp(
-                    -half_height + HOLE_RADIUS,
-                    (half_height - TOP_MARGIN) - HOLE_RADIUS,
-                );
+                let max_x = (half_width - hole.radius).max(0.0);
+                let min_y = -half_height + hole.radius;
+                let max_y = (half_height - TOP_MARGIN - hole.radius).max(min_y);
+                let clamped_x = target.x.clamp(-max_x, max_x);
+                let clamped_y = target.y.clamp(min_y, max_y);
                 let final_target = Vec3::new(clamped_x, clamped_y, target.z);
 
                 transform.translation = final_target;
@@ -223,13 +368,11 @@ fn move_holes(
             let target = transform.translation + displacement;
 
             // Clamp target to ensure the entire hole remains within visible bounds.
-            let clamped_x = target
-                .x
-                .clamp(-half_width + HOLE_RADIUS, half_width - HOLE_RADIUS);
-            let clamped_y = target.y.clamp(
-                -half_height + HOLE_RADIUS,
-                (half_height - TOP_MARGIN) - HOLE_RADIUS,
-            );
+            let max_x = (half_width - hole.radius).max(0.0);
+            let min_y = -half_height + hole.radius;
+            let max_y = (half_height - TOP_MARGIN - hole.radius).max(min_y);
+            let clamped_x = target.x.clamp(-max_x, max_x);
+            let clamped_y = target.y.clamp(min_y, max_y);
             let final_target = Vec3::new(clamped_x, clamped_y, target.z);
 
             transform.translation = final_target;
@@ -239,29 +382,79 @@ fn move_holes(
 
 fn check_missile_hit(
     mut commands: Commands,
-    mut missiles: Query<(Entity, &Transform), With<Missile>>,
-    holes: Query<(Entity, &Transform), (With<Hole>, Without<Missile>)>,
-    mut sfx_play_ew: EventWriter<GlobalPlayEvent>,
+    mut collisions: EventReader<CollisionEvent>,
+    missiles: Query<&Missile>,
+    mut holes: Query<(&mut Hole, &Transform)>,
+    mut destroyed: EventWriter<HoleDestroyed>,
+    mut sfx_play_ew: EventWriter<PlayEvent<SfxChannel>>,
+) {
+    for collision in collisions.read() {
+        let Some((missile, hole)) = started_with(collision, |entity| missiles.contains(entity))
+        else {
+            continue;
+        };
+        let (Ok(&Missile { shooter, .. }), Ok((mut state, transform))) =
+            (missiles.get(missile), holes.get_mut(hole))
+        else {
+            continue;
+        };
+        // Already destroyed by another missile this tick.
+        if state.hit_points == 0 {
+            continue;
+        }
+        commands.entity(missile).despawn();
+        state.hit_points -= 1;
+        if state.hit_points > 0 {
+            continue;
+        }
+        commands.entity(hole).despawn();
+        destroyed.send(HoleDestroyed {
+            position: transform.translation.truncate(),
+            shooter,
+        });
+        let event = PlayEvent::new(AudioFiles::ExplosionWAV).with_settings(PlaybackSettings {
+            mode: PlaybackMode::Once,
+            volume: Volume::new(10.),
+            ..Default::default()
+        });
+        sfx_play_ew.send(event);
+    }
+}
+
+fn on_hole_collapsed(
+    mut events: EventReader<HoleCollapsed>,
+    mut sprites: Query<&mut Sprite, With<Hole>>,
+    mut sfx_play_ew: EventWriter<PlayEvent<SfxChannel>>,
+) {
+    for collapse in events.read() {
+        if let Ok(mut sprite) = sprites.get_mut(collapse.hole) {
+            sprite.color = HoleKind::Well.color();
+        }
+        let event =
+            PlayEvent::new(AudioFiles::ExplosionCrunch001OGG).with_settings(PlaybackSettings::ONCE);
+        sfx_play_ew.send(event);
+    }
+}
+
+// The pull fades linearly to nothing at `well_radius`. It is scaled by mass,
+// so `well_strength` is the same acceleration for the player and missiles.
+fn apply_gravity_wells(
+    tuning: Res<Tuning>,
+    wells: Query<&Transform, With<GravityWell>>,
+    mut bodies: Query<(&Transform, &ReadMassProperties, &mut ExternalForce)>,
 ) {
-    //check if missile hits hole
-    for (missile_entity, missile_transform) in missiles.iter_mut() {
-        for (hole_enityt, hole_transform) in holes.iter() {
-            let distance = missile_transform
-                .translation
-                .distance(hole_transform.translation);
-            if distance < 40. {
-                commands.entity(missile_entity).despawn();
-                commands.entity(hole_enityt).despawn();
-                let event = GlobalPlayEvent::new(AudioFiles::ExplosionWAV).with_settings(
-                    PlaybackSettings {
-                        mode: PlaybackMode::Once,
-                        volume: Volume::new(10.),
-                        ..Default::default()
-                    },
-                );
-                sfx_play_ew.send(event);
+    let radius = tuning.holes.well_radius;
+    for (transform, mass, mut force) in bodies.iter_mut() {
+        let position = transform.translation.truncate();
+        let mut pull = Vec2::ZERO;
+        for well in wells.iter() {
+            let offset = well.translation.truncate() - position;
+            let distance = offset.length();
+            if distance > 0.0 && distance < radius {
+                pull += offset / distance * tuning.holes.well_strength * (1.0 - distance / radius);
             }
         }
+        force.force = pull * mass.get().mass;
     }
 }
 
@@ -271,14 +464,20 @@ pub struct HolePlugin;
 
 impl Plugin for HolePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Start), cleanup_holes)
+        app.init_resource::<HoleSpawnTimer>()
+            .add_event::<HoleDestroyed>()
+            .add_event::<HoleCollapsed>()
+            .add_systems(OnEnter(GameState::Start), cleanup_holes)
             .add_systems(
                 FixedUpdate,
                 (
-                    spawn_hole,
+                    tick_hole_spawn_timer,
+                    spawn_hole.in_set(RngSet::Holes),
                     check_hole_star_collision,
-                    check_hole_collisions,
+                    merge_holes,
+                    on_hole_collapsed,
                     move_holes,
+                    apply_gravity_wells,
                     check_missile_hit,
                 )
                     .chain()
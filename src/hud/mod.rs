@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+
+use crate::{
+    coop::CoopSettings,
+    player::{Out, Player, PLAYER_COLORS},
+    powerup::{countdowns, ActivePowerUps},
+    score::Score,
+    AppState,
+};
+
+/// Lives past this are shown as one ball and a count.
+const MAX_LIFE_ICONS: i8 = 5;
+
+// COMPONENTS -------------------------------------
+
+#[derive(Component)]
+struct ScoreText;
+
+/// Holds one panel per player, in the top right.
+#[derive(Component)]
+struct PlayerPanels;
+
+/// Stats, lives and power-ups of `player`, one above the other.
+#[derive(Component)]
+struct PlayerPanel {
+    player: Entity,
+}
+
+/// Stars, missiles and lives of `player`.
+#[derive(Component)]
+struct PanelStats {
+    player: Entity,
+}
+
+/// Countdowns of the power-ups running on `player`.
+#[derive(Component)]
+struct PanelPowerUps {
+    player: Entity,
+}
+
+// SYSTEMS -------------------------------------
+
+fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        Text::new("Score: 0"),
+        TextFont {
+            font_size: 20.,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            left: Val::Px(5.0),
+            ..default()
+        },
+        ScoreText,
+        StateScoped(AppState::InGame),
+    ));
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            right: Val::Px(5.0),
+            column_gap: Val::Px(20.0),
+            ..default()
+        },
+        PlayerPanels,
+        StateScoped(AppState::InGame),
+    ));
+}
+
+fn update_score_text(mut text_query: Query<&mut Text, With<ScoreText>>, score: Res<Score>) {
+    for mut text in text_query.iter_mut() {
+        text.0 = format!("Score: {}", score.points);
+        if score.combo > 1 {
+            text.0 += &format!("  x{}", score.combo);
+        }
+    }
+}
+
+fn add_player_panels(
+    mut commands: Commands,
+    players: Query<(Entity, &Player), Added<Player>>,
+    root: Single<Entity, With<PlayerPanels>>,
+) {
+    let mut players: Vec<_> = players.iter().collect();
+    players.sort_by_key(|(_, player)| player.slot);
+    for (entity, player) in players {
+        commands.entity(*root).with_children(|root| {
+            root.spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                PlayerPanel { player: entity },
+            ))
+            .with_children(|panel| {
+                panel.spawn((
+                    Node {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(4.0),
+                        ..default()
+                    },
+                    PanelStats { player: entity },
+                ));
+                panel.spawn((
+                    Text::default(),
+                    TextFont {
+                        font_size: 20.,
+                        ..default()
+                    },
+                    TextColor(PLAYER_COLORS[player.slot]),
+                    PanelPowerUps { player: entity },
+                ));
+            });
+        });
+    }
+}
+
+// The players went away with a restart.
+fn remove_player_panels(
+    mut commands: Commands,
+    panels: Query<(Entity, &PlayerPanel)>,
+    players: Query<(), With<Player>>,
+) {
+    for (panel, PlayerPanel { player }) in panels.iter() {
+        if !players.contains(*player) {
+            commands.entity(panel).despawn_recursive();
+        }
+    }
+}
+
+// Rebuilt whenever the player changes.
+fn update_player_panels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    coop: Res<CoopSettings>,
+    stats: Query<(Entity, &PanelStats)>,
+    players: Query<(Ref<Player>, Has<Out>)>,
+) {
+    for (stats, PanelStats { player }) in stats.iter() {
+        let Ok((player, out)) = players.get(*player) else {
+            continue;
+        };
+        if !player.is_changed() {
+            continue;
+        }
+
+        let color = PLAYER_COLORS[player.slot];
+        let mut label = if coop.players > 1 {
+            format!("P{}  ", player.slot + 1)
+        } else {
+            String::new()
+        };
+        if out {
+            label += "Out";
+        } else {
+            label += &format!("Stars: {}  Missiles: {}", player.stars, player.missiles);
+        }
+
+        let life = player.life.max(0);
+        commands
+            .entity(stats)
+            .despawn_descendants()
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(label),
+                    TextFont {
+                        font_size: 20.,
+                        ..default()
+                    },
+                    TextColor(color),
+                ));
+                let icons = if life > MAX_LIFE_ICONS { 1 } else { life };
+                for _ in 0..icons {
+                    parent.spawn((
+                        ImageNode {
+                            image: asset_server.load("ball_blue_small.png"),
+                            color,
+                            ..default()
+                        },
+                        Node {
+                            width: Val::Px(24.0),
+                            height: Val::Px(24.0),
+                            ..default()
+                        },
+                    ));
+                }
+                if life > MAX_LIFE_ICONS {
+                    parent.spawn((
+                        Text::new(format!("x{}", life)),
+                        TextFont {
+                            font_size: 20.,
+                            ..default()
+                        },
+                        TextColor(color),
+                    ));
+                }
+            });
+    }
+}
+
+fn update_power_up_text(
+    players: Query<ActivePowerUps, With<Player>>,
+    mut texts: Query<(&PanelPowerUps, &mut Text)>,
+) {
+    for (PanelPowerUps { player }, mut text) in texts.iter_mut() {
+        if let Ok(active) = players.get(*player) {
+            text.0 = countdowns(active).join("  ");
+        }
+    }
+}
+
+// PLUGIN -------------------------------------
+
+/// The score in the top left and a panel per player, with their power-ups,
+/// in the top right.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), spawn_hud)
+            .add_systems(
+                Update,
+                (
+                    add_player_panels,
+                    remove_player_panels,
+                    update_player_panels,
+                    update_power_up_text,
+                    update_score_text,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
@@ -0,0 +1,471 @@
+use bevy::{
+    input::InputSystem,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const BINDINGS_KEY: &str = "bindings";
+
+/// Cancels a rebind, so it can't be bound to anything itself.
+pub const CANCEL_REBIND_KEY: KeyCode = KeyCode::Backspace;
+/// Cancels a rebind from a gamepad, so it can't be bound to anything itself.
+pub const CANCEL_REBIND_BUTTON: GamepadButton = GamepadButton::Select;
+
+// EVENTS -------------------------------------
+
+/// Waits for the next key or gamepad button and assigns it to `target`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StartRebind(pub RebindTarget);
+
+/// Sent after a rebind has been applied and saved, or cancelled.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BindingsChanged;
+
+// BINDINGS -------------------------------------
+
+/// Keyboard slots a player can remap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyBinding {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    AimUp,
+    AimDown,
+    AimLeft,
+    AimRight,
+    Fire,
+    Pause,
+}
+
+/// Gamepad buttons a player can remap. The sticks are fixed: left moves,
+/// right aims.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PadBinding {
+    Fire,
+    Pause,
+}
+
+impl KeyBinding {
+    pub const ALL: [KeyBinding; 10] = [
+        KeyBinding::MoveUp,
+        KeyBinding::MoveDown,
+        KeyBinding::MoveLeft,
+        KeyBinding::MoveRight,
+        KeyBinding::AimUp,
+        KeyBinding::AimDown,
+        KeyBinding::AimLeft,
+        KeyBinding::AimRight,
+        KeyBinding::Fire,
+        KeyBinding::Pause,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyBinding::MoveUp => "Move Up",
+            KeyBinding::MoveDown => "Move Down",
+            KeyBinding::MoveLeft => "Move Left",
+            KeyBinding::MoveRight => "Move Right",
+            KeyBinding::AimUp => "Aim Up",
+            KeyBinding::AimDown => "Aim Down",
+            KeyBinding::AimLeft => "Aim Left",
+            KeyBinding::AimRight => "Aim Right",
+            KeyBinding::Fire => "Fire",
+            KeyBinding::Pause => "Pause",
+        }
+    }
+}
+
+impl PadBinding {
+    pub const ALL: [PadBinding; 2] = [PadBinding::Fire, PadBinding::Pause];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PadBinding::Fire => "Gamepad Fire",
+            PadBinding::Pause => "Gamepad Pause",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebindTarget {
+    Key(KeyBinding),
+    Pad(PadBinding),
+}
+
+impl RebindTarget {
+    pub fn label(self) -> &'static str {
+        match self {
+            RebindTarget::Key(binding) => binding.label(),
+            RebindTarget::Pad(binding) => binding.label(),
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub move_up: KeyCode,
+    pub move_down: KeyCode,
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    pub aim_up: KeyCode,
+    pub aim_down: KeyCode,
+    pub aim_left: KeyCode,
+    pub aim_right: KeyCode,
+    pub fire: KeyCode,
+    pub pause: KeyCode,
+    pub gamepad_fire: GamepadButton,
+    pub gamepad_pause: GamepadButton,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        InputBindings {
+            move_up: KeyCode::KeyW,
+            move_down: KeyCode::KeyS,
+            move_left: KeyCode::KeyA,
+            move_right: KeyCode::KeyD,
+            aim_up: KeyCode::ArrowUp,
+            aim_down: KeyCode::ArrowDown,
+            aim_left: KeyCode::ArrowLeft,
+            aim_right: KeyCode::ArrowRight,
+            fire: KeyCode::Space,
+            pause: KeyCode::Escape,
+            gamepad_fire: GamepadButton::South,
+            gamepad_pause: GamepadButton::Start,
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn key(&self, action: KeyBinding) -> KeyCode {
+        match action {
+            KeyBinding::MoveUp => self.move_up,
+            KeyBinding::MoveDown => self.move_down,
+            KeyBinding::MoveLeft => self.move_left,
+            KeyBinding::MoveRight => self.move_right,
+            KeyBinding::AimUp => self.aim_up,
+            KeyBinding::AimDown => self.aim_down,
+            KeyBinding::AimLeft => self.aim_left,
+            KeyBinding::AimRight => self.aim_right,
+            KeyBinding::Fire => self.fire,
+            KeyBinding::Pause => self.pause,
+        }
+    }
+
+    pub fn pad_button(&self, action: PadBinding) -> GamepadButton {
+        match action {
+            PadBinding::Fire => self.gamepad_fire,
+            PadBinding::Pause => self.gamepad_pause,
+        }
+    }
+
+    /// Human readable name of whatever is bound to `target`, e.g. `W` or `South`.
+    pub fn describe(&self, target: RebindTarget) -> String {
+        match target {
+            RebindTarget::Key(binding) => {
+                let name = format!("{:?}", self.key(binding));
+                name.strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name)
+                    .to_string()
+            }
+            RebindTarget::Pad(binding) => format!("{:?}", self.pad_button(binding)),
+        }
+    }
+
+    /// Binds `key` to `action`. An action that already used `key` gets the
+    /// one `action` had instead, so no key does two things.
+    pub fn bind_key(&mut self, action: KeyBinding, key: KeyCode) {
+        let previous = std::mem::replace(self.key_mut(action), key);
+        for other in KeyBinding::ALL {
+            if other != action && self.key(other) == key {
+                *self.key_mut(other) = previous;
+            }
+        }
+    }
+
+    /// Binds `button` to `action`, swapping like `bind_key`.
+    pub fn bind_pad_button(&mut self, action: PadBinding, button: GamepadButton) {
+        let previous = std::mem::replace(self.pad_button_mut(action), button);
+        for other in PadBinding::ALL {
+            if other != action && self.pad_button(other) == button {
+                *self.pad_button_mut(other) = previous;
+            }
+        }
+    }
+
+    fn key_mut(&mut self, action: KeyBinding) -> &mut KeyCode {
+        match action {
+            KeyBinding::MoveUp => &mut self.move_up,
+            KeyBinding::MoveDown => &mut self.move_down,
+            KeyBinding::MoveLeft => &mut self.move_left,
+            KeyBinding::MoveRight => &mut self.move_right,
+            KeyBinding::AimUp => &mut self.aim_up,
+            KeyBinding::AimDown => &mut self.aim_down,
+            KeyBinding::AimLeft => &mut self.aim_left,
+            KeyBinding::AimRight => &mut self.aim_right,
+            KeyBinding::Fire => &mut self.fire,
+            KeyBinding::Pause => &mut self.pause,
+        }
+    }
+
+    fn pad_button_mut(&mut self, action: PadBinding) -> &mut GamepadButton {
+        match action {
+            PadBinding::Fire => &mut self.gamepad_fire,
+            PadBinding::Pause => &mut self.gamepad_pause,
+        }
+    }
+}
+
+// RESOURCES -------------------------------------
+
+/// What the player asked for this frame, from the keyboard and every gamepad
+/// together.
+///
+/// Devices are sampled into this once per frame. Menus read it directly,
+/// players get theirs through their `Controller`.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ActionState {
+    /// Move: desired direction, at most unit length.
+    pub movement: Vec2,
+    /// Aim: direction to fire in. Missiles go the way the player faces when unset.
+    pub aim: Option<Vec2>,
+    /// Fire was pressed this frame.
+    pub fire: bool,
+    /// Pause was pressed this frame.
+    pub pause: bool,
+}
+
+/// The same as `ActionState`, but for each device on its own, for co-op
+/// players that have one device each.
+#[derive(Resource, Debug, Default)]
+pub struct DeviceActions {
+    pub keyboard: ActionState,
+    pub gamepads: HashMap<Entity, ActionState>,
+}
+
+/// Fire presses not yet seen by a fixed tick.
+///
+/// Presses are read once per frame but players act once per `FixedUpdate`
+/// tick, which can run no times or several times in a frame. Each press is
+/// held here until the next tick has run, so it fires exactly once.
+#[derive(Resource, Debug, Default)]
+pub struct LatchedFire {
+    /// From any device, for a single player.
+    pub any: bool,
+    pub keyboard: bool,
+    pub gamepads: HashSet<Entity>,
+}
+
+#[derive(Resource, Default)]
+struct PendingRebind(Option<RebindTarget>);
+
+/// One device's sticks or keys before they are clamped, so several can be
+/// added together.
+#[derive(Clone, Copy)]
+struct Reading {
+    movement: Vec2,
+    aim: Vec2,
+    fire: bool,
+    pause: bool,
+}
+
+impl Reading {
+    fn merge(self, other: Reading) -> Reading {
+        Reading {
+            movement: self.movement + other.movement,
+            aim: self.aim + other.aim,
+            fire: self.fire || other.fire,
+            pause: self.pause || other.pause,
+        }
+    }
+
+    fn actions(self) -> ActionState {
+        ActionState {
+            movement: self.movement.clamp_length_max(1.0),
+            aim: (self.aim.length() > 0.1).then(|| self.aim.normalize()),
+            fire: self.fire,
+            pause: self.pause,
+        }
+    }
+}
+
+// SYSTEMS -------------------------------------
+
+fn key_axis(
+    keys: &ButtonInput<KeyCode>,
+    up: KeyCode,
+    down: KeyCode,
+    left: KeyCode,
+    right: KeyCode,
+) -> Vec2 {
+    let mut axis = Vec2::ZERO;
+    if keys.pressed(up) {
+        axis.y += 1.0;
+    }
+    if keys.pressed(down) {
+        axis.y -= 1.0;
+    }
+    if keys.pressed(left) {
+        axis.x -= 1.0;
+    }
+    if keys.pressed(right) {
+        axis.x += 1.0;
+    }
+    axis
+}
+
+fn update_action_state(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    bindings: Res<InputBindings>,
+    pending: Res<PendingRebind>,
+    mut actions: ResMut<ActionState>,
+    mut devices: ResMut<DeviceActions>,
+    mut latched: ResMut<LatchedFire>,
+) {
+    *actions = ActionState::default();
+    devices.keyboard = ActionState::default();
+    devices.gamepads.clear();
+    // The key being bound must not also act in the game.
+    if pending.0.is_some() {
+        return;
+    }
+
+    let keyboard = Reading {
+        movement: key_axis(
+            &keys,
+            bindings.move_up,
+            bindings.move_down,
+            bindings.move_left,
+            bindings.move_right,
+        ),
+        aim: key_axis(
+            &keys,
+            bindings.aim_up,
+            bindings.aim_down,
+            bindings.aim_left,
+            bindings.aim_right,
+        ),
+        fire: keys.just_pressed(bindings.fire),
+        pause: keys.just_pressed(bindings.pause),
+    };
+    devices.keyboard = keyboard.actions();
+    latched.keyboard |= keyboard.fire;
+
+    let mut combined = keyboard;
+    for (entity, gamepad) in &gamepads {
+        let reading = Reading {
+            movement: gamepad.left_stick(),
+            aim: gamepad.right_stick(),
+            fire: gamepad.just_pressed(bindings.gamepad_fire),
+            pause: gamepad.just_pressed(bindings.gamepad_pause),
+        };
+        devices.gamepads.insert(entity, reading.actions());
+        if reading.fire {
+            latched.gamepads.insert(entity);
+        }
+        combined = combined.merge(reading);
+    }
+    *actions = combined.actions();
+    latched.any |= actions.fire;
+}
+
+fn clear_latched_fire(mut latched: ResMut<LatchedFire>) {
+    *latched = LatchedFire::default();
+}
+
+fn start_rebind(mut events: EventReader<StartRebind>, mut pending: ResMut<PendingRebind>) {
+    for StartRebind(target) in events.read() {
+        pending.0 = Some(*target);
+    }
+}
+
+fn capture_rebind(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut pending: ResMut<PendingRebind>,
+    mut bindings: ResMut<InputBindings>,
+    mut changed: EventWriter<BindingsChanged>,
+) {
+    let Some(target) = pending.0 else {
+        return;
+    };
+    let cancelled = keys.just_pressed(CANCEL_REBIND_KEY)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(CANCEL_REBIND_BUTTON));
+    if cancelled {
+        pending.0 = None;
+        changed.send(BindingsChanged);
+        return;
+    }
+    match target {
+        RebindTarget::Key(action) => {
+            let Some(key) = keys.get_just_pressed().next() else {
+                return;
+            };
+            bindings.bind_key(action, *key);
+        }
+        RebindTarget::Pad(action) => {
+            let Some(button) = gamepads
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next())
+            else {
+                return;
+            };
+            bindings.bind_pad_button(action, *button);
+        }
+    }
+    pending.0 = None;
+    storage::save(BINDINGS_KEY, &*bindings);
+    changed.send(BindingsChanged);
+}
+
+// PLUGIN -------------------------------------
+pub struct PlayerInputPlugin;
+
+impl Plugin for PlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        let bindings = storage::load::<InputBindings>(BINDINGS_KEY).unwrap_or_default();
+        app.insert_resource(bindings)
+            .init_resource::<ActionState>()
+            .init_resource::<DeviceActions>()
+            .init_resource::<LatchedFire>()
+            .init_resource::<PendingRebind>()
+            .add_event::<StartRebind>()
+            .add_event::<BindingsChanged>()
+            .add_systems(
+                PreUpdate,
+                (start_rebind, update_action_state, capture_rebind)
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(FixedPostUpdate, clear_latched_fire);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_a_used_key_swaps_it() {
+        let mut bindings = InputBindings::default();
+        bindings.bind_key(KeyBinding::Fire, KeyCode::KeyW);
+        assert_eq!(bindings.fire, KeyCode::KeyW);
+        assert_eq!(bindings.move_up, KeyCode::Space);
+    }
+
+    #[test]
+    fn binding_a_used_button_swaps_it() {
+        let mut bindings = InputBindings::default();
+        bindings.bind_pad_button(PadBinding::Pause, GamepadButton::South);
+        assert_eq!(bindings.gamepad_pause, GamepadButton::South);
+        assert_eq!(bindings.gamepad_fire, GamepadButton::Start);
+    }
+}
@@ -1,169 +1,170 @@
+mod arena;
+mod attract;
 mod audio;
+mod button;
+mod cli;
+mod controller;
+mod coop;
+mod headless;
+mod highscore;
 mod holes;
+mod hud;
+mod input;
+mod menu;
+mod particles;
 mod player;
+mod powerup;
+mod replay;
+mod rng;
+mod score;
+mod shake;
+mod slider;
 mod star;
+mod storage;
+mod tuning;
+mod wave;
 
+use arena::*;
+use attract::*;
 use audio::*;
 use bevy::{
-    asset::AssetMetaCheck, audio::PlaybackSettings, color::palettes::css::DARK_GREY, prelude::*,
-    sprite::AlphaMode2d,
+    asset::AssetMetaCheck, color::palettes::css::DARK_GREY, prelude::*, sprite::AlphaMode2d,
 };
 use bevy_audio_controller::prelude::*;
 use bevy_rapier2d::prelude::*;
+use button::*;
+use cli::*;
+use controller::*;
+use coop::*;
+use headless::*;
+use highscore::*;
 use holes::*;
+use hud::*;
+use input::*;
+use menu::*;
+use particles::*;
 use player::*;
+use powerup::*;
+use replay::*;
+use rng::*;
+use score::*;
+use shake::*;
+use slider::*;
 use star::*;
-
-#[derive(Component, Default, AudioChannel, Reflect)]
-struct SfxChannel;
-
-#[derive(Component)]
-struct Health;
-
-#[derive(Event)]
-enum ButtonClicked {
-    ExitGame,
-    StartGame,
-    PauseGame,
-    ResumeGame,
-    RestartGame,
+use tuning::*;
+use wave::*;
+
+/// Top level screens. Everything spawned for a run is scoped to `InGame`
+/// and goes away when the app leaves it.
+#[derive(States, Default, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+enum AppState {
+    #[default]
+    Loading,
+    MainMenu,
+    InGame,
 }
 
-#[derive(Component)]
-struct ScoreText;
-
-#[derive(Resource, States, Debug, Hash, PartialEq, Eq, Clone)]
+/// Where the current run is. Only exists while in `AppState::InGame`, and
+/// every visit to `Start` begins a fresh run.
+#[derive(SubStates, Default, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[source(AppState = AppState::InGame)]
 enum GameState {
+    #[default]
     Start,
     Playing,
-    GameOver,
     Paused,
+    GameOver,
 }
-impl Default for GameState {
-    fn default() -> Self {
-        GameState::Start
-    }
-}
-
-#[derive(Component)]
-struct StartButton;
-
-#[derive(Component)]
-struct MainMenu;
-
-#[derive(Component)]
-struct GameOverMenu;
-
-const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
-const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
 
 fn main() {
+    let cli = CliArgs::parse();
+    if cli.headless {
+        App::new()
+            .insert_resource(cli.rng_seed())
+            .insert_resource(cli)
+            .add_plugins(HeadlessPlugin)
+            .run();
+        return;
+    }
+
     App::new()
+        .insert_resource(cli.rng_seed())
+        .insert_resource(cli)
         .add_plugins(DefaultPlugins.set(AssetPlugin {
             meta_check: AssetMetaCheck::Never,
             ..AssetPlugin::default()
         }))
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+        .init_state::<AppState>()
+        .add_sub_state::<GameState>()
+        .enable_state_scoped_entities::<AppState>()
+        .enable_state_scoped_entities::<GameState>()
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0).in_fixed_schedule())
         .add_plugins(RapierDebugRenderPlugin::default())
         .add_plugins(AudioControllerPlugin)
         .add_systems(Startup, (setup).chain())
+        .add_plugins(ArenaPlugin)
+        .add_plugins(TuningPlugin)
+        .add_plugins(RngPlugin)
+        .add_plugins(PlayerInputPlugin)
+        .add_plugins(CoopPlugin)
+        .add_plugins(ReplayPlugin)
+        .add_plugins(ControllerPlugin)
         .add_plugins(PlayerPlugin)
         .add_plugins(StarPlugin)
         .add_plugins(HolePlugin)
-        .add_plugins(BackgroundAudioPlugin)
-        .register_audio_channel::<SfxChannel>()
-        .insert_state(GameState::Start)
-        .init_resource::<GameState>()
-        //.configure_sets(Update, RollyPollySet.run_if(in_state(GameState::Playing)))
+        .add_plugins(WavePlugin)
+        .add_plugins(PowerUpPlugin)
+        .add_plugins(ScorePlugin)
+        .add_plugins(HudPlugin)
+        .add_plugins(ParticlePlugin)
+        .add_plugins(ShakePlugin)
+        .add_plugins(HighScorePlugin)
+        .add_plugins(ButtonPlugin)
+        .add_plugins(SliderPlugin)
+        .add_plugins(MenuPlugin)
+        .add_plugins(AttractPlugin)
+        .add_plugins(GameAudioPlugin)
         .add_systems(
             Update,
-            (button_system, button_events)
-                .chain()
-                .run_if(in_state(GameState::Paused)),
+            finish_loading.run_if(in_state(AppState::Loading).and(tuning_ready)),
         )
-        .add_systems(OnEnter(GameState::Start), (setup_game, spawn_score_text))
-        .add_systems(
-            Update,
-            (game_over_menu, button_events, button_system)
-                .chain()
-                .run_if(in_state(GameState::GameOver)),
-        )
-        .add_systems(
-            Update,
-            (update_score_text,)
-                .chain()
-                .run_if(in_state(GameState::Playing)),
-        )
-        .add_event::<ButtonClicked>()
+        .add_systems(OnEnter(AppState::InGame), spawn_arena)
+        .add_systems(OnEnter(GameState::Start), setup_game)
+        .add_systems(OnEnter(GameState::Paused), freeze_physics)
+        .add_systems(OnExit(GameState::Paused), resume_physics)
         .run();
 }
 
-fn setup(
-    mut commands: Commands,
-    mut next_state: ResMut<NextState<GameState>>,
-    asset_server: Res<AssetServer>,
-) {
-    commands.insert_resource(GameState::Start);
-    commands.insert_resource(GameState::GameOver);
-    commands.insert_resource(GameState::Paused);
-    commands.insert_resource(GameState::Playing);
+fn setup(mut commands: Commands) {
     commands.spawn(Camera2d::default());
-    start_menu(&mut commands, asset_server);
-    next_state.set(GameState::Paused);
 }
 
-fn setup_game(
+fn finish_loading(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::MainMenu);
+}
+
+fn spawn_arena(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    window: Query<&Window>,
-    mut next_state: ResMut<NextState<GameState>>,
-    health: Query<Entity, With<Health>>,
+    bounds: Res<ArenaBounds>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    let window = window.single();
     let texture_handle = asset_server.load("space.png");
-    let mesh_handle = meshes.add(Rectangle::from_size(Vec2::new(
-        window.width(),
-        window.height(),
-    )));
-
-    let half_width = window.width() / 2.0;
-    let half_height = window.height() / 2.0;
-    let thickness = 10.0; // thickness for the boundaries
-
-    // Top boundary
-    commands.spawn((
-        Transform::from_xyz(0.0, (half_height - 40.) + thickness / 2.0, 0.0),
-        GlobalTransform::default(),
-        RigidBody::Fixed,
-        Collider::cuboid(half_width, thickness / 2.0),
-    ));
-
-    // Bottom boundary
-    commands.spawn((
-        Transform::from_xyz(0.0, -half_height - thickness / 2.0, 0.0),
-        GlobalTransform::default(),
-        RigidBody::Fixed,
-        Collider::cuboid(half_width, thickness / 2.0),
-    ));
-
-    // Left boundary
-    commands.spawn((
-        Transform::from_xyz(-half_width - thickness / 2.0, 0.0, 0.0),
-        GlobalTransform::default(),
-        RigidBody::Fixed,
-        Collider::cuboid(thickness / 2.0, half_height),
-    ));
+    // A unit square, scaled to the arena by `ArenaBackground`.
+    let mesh_handle = meshes.add(Rectangle::from_size(Vec2::ONE));
 
-    // Right boundary
-    commands.spawn((
-        Transform::from_xyz(half_width + thickness / 2.0, 0.0, 0.0),
-        GlobalTransform::default(),
-        RigidBody::Fixed,
-        Collider::cuboid(thickness / 2.0, half_height),
-    ));
+    for wall in ArenaWall::ALL {
+        let (transform, collider) = wall.placement(&bounds);
+        commands.spawn((
+            transform,
+            RigidBody::Fixed,
+            collider,
+            CollisionGroups::new(WALL_GROUP, Group::ALL),
+            wall,
+            StateScoped(AppState::InGame),
+        ));
+    }
 
     // Background
     commands.spawn((
@@ -173,325 +174,29 @@ fn setup_game(
             alpha_mode: AlphaMode2d::Mask(0.9),
             texture: Some(texture_handle.clone()),
         })),
-        Transform::from_translation(Vec3::new(0., -50., -1.0)),
-    ));
-
-    for health in health.iter() {
-        commands.entity(health).despawn_recursive();
-    }
-
-    // Spawn health indicators
-    for i in 0..3 {
-        let position = Vec3::new(
-            window.width() / 2.0 - 20.0 - (i as f32 * 35.0),
-            window.height() / 2.0 - 20.0,
-            0.,
-        );
-
-        commands.spawn((
-            Sprite::from_image(asset_server.load("ball_blue_small.png")),
-            Transform::from_translation(position),
-            Health,
-        ));
-    }
-    next_state.set(GameState::Playing);
-}
-
-fn spawn_score_text(mut commands: Commands) {
-    commands.spawn((
-        Text::new("Start: 0"),
-        TextFont {
-            font_size: 20.,
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        TextLayout::new_with_justify(JustifyText::Center),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Px(5.0),
-            left: Val::Px(5.0),
-            ..default()
-        },
-        ScoreText,
+        Transform::from_translation(Vec3::new(0., -50., -1.0)).with_scale(Vec3::new(
+            bounds.width,
+            bounds.height,
+            1.0,
+        )),
+        ArenaBackground,
+        StateScoped(AppState::InGame),
     ));
 }
 
-fn start_menu(commands: &mut Commands, asset_server: Res<AssetServer>) {
-    commands
-        .spawn((
-            Node {
-                position_type: PositionType::Relative,
-                display: Display::Flex,
-                flex_direction: FlexDirection::Column,
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                width: Val::Percent(100.0),
-                height: Val::Percent(100.0),
-                ..default()
-            },
-            MainMenu,
-        ))
-        .with_children(|parent| {
-            // Image
-            parent
-                .spawn((Node {
-                    width: Val::Auto,
-                    height: Val::Auto,
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    margin: UiRect::bottom(Val::Px(5.0)), // Add some space below the image
-                    ..default()
-                },))
-                .with_child((
-                    ImageNode {
-                        image: asset_server.load("title.png"),
-                        ..ImageNode::default()
-                    },
-                    Transform::from_scale(Vec3::splat(0.7)), // Scale down the image
-                ));
-
-            // Start Game Button
-            parent
-                .spawn((
-                    Button,
-                    Node {
-                        width: Val::Auto,
-                        height: Val::Auto,
-                        padding: UiRect::all(Val::Px(10.)),
-                        border: UiRect::all(Val::Px(5.0)),
-                        margin: UiRect::bottom(Val::Px(5.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    BorderColor(Color::BLACK),
-                    BorderRadius::MAX,
-                    BackgroundColor(NORMAL_BUTTON),
-                    StartButton,
-                ))
-                .with_child((
-                    Text::new("Start Game"),
-                    TextFont {
-                        font_size: 33.0,
-                        ..default()
-                    },
-                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                ));
-
-            // Quit Game Button
-            parent
-                .spawn((
-                    Button,
-                    Node {
-                        width: Val::Auto,
-                        height: Val::Auto,
-                        padding: UiRect::all(Val::Px(10.)),
-                        border: UiRect::all(Val::Px(5.0)),
-                        margin: UiRect::bottom(Val::Px(5.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    BorderColor(Color::BLACK),
-                    BorderRadius::MAX,
-                    BackgroundColor(NORMAL_BUTTON),
-                ))
-                .with_child((
-                    Text::new("Quit Game"),
-                    TextFont {
-                        font_size: 33.0,
-                        ..default()
-                    },
-                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                ));
-        });
-}
-
-fn game_over_menu(mut commands: Commands) {
-    commands
-        .spawn((
-            Node {
-                position_type: PositionType::Relative,
-                display: Display::Grid,
-                grid_template_rows: RepeatedGridTrack::auto(2), // 2 auto rows
-                row_gap: Val::Px(4.),
-                margin: UiRect {
-                    left: Val::Auto,
-                    right: Val::Auto,
-                    top: Val::Auto,
-                    bottom: Val::Auto,
-                },
-                padding: UiRect::all(Val::Px(10.0)),
-                width: Val::Percent(30.0),
-                height: Val::Percent(30.0),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            BackgroundColor(Color::hsla(1., 1., 1., 0.8)),
-            GameOverMenu,
-        ))
-        .with_children(|parent| {
-            // First row - Quit Game button
-            parent
-                .spawn((
-                    Button,
-                    Node {
-                        width: Val::Auto,
-                        height: Val::Auto,
-                        padding: UiRect::all(Val::Px(10.0)),
-                        border: UiRect::all(Val::Px(5.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    BorderColor(Color::BLACK),
-                    BorderRadius::MAX,
-                    BackgroundColor(NORMAL_BUTTON),
-                ))
-                .with_child((
-                    Text::new("Quit Game"),
-                    TextFont {
-                        font_size: 33.0,
-                        ..default()
-                    },
-                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                ));
-
-            // Second row - Start Over button
-            parent
-                .spawn((
-                    Button,
-                    Node {
-                        width: Val::Auto,
-                        height: Val::Auto,
-                        padding: UiRect::all(Val::Px(10.0)),
-                        border: UiRect::all(Val::Px(5.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    BorderColor(Color::BLACK),
-                    BorderRadius::MAX,
-                    BackgroundColor(NORMAL_BUTTON),
-                ))
-                .with_child((
-                    Text::new("Start Over"),
-                    TextFont {
-                        font_size: 33.0,
-                        ..default()
-                    },
-                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                ));
-        });
-}
-
-fn button_system(
-    mut interaction_query: Query<
-        (
-            &Interaction,
-            &mut BackgroundColor,
-            &mut BorderColor,
-            &Children,
-        ),
-        (Changed<Interaction>, With<Button>),
-    >,
-    mut event_writter: EventWriter<ButtonClicked>,
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    text: Query<&Text>,
-    mut sfx_play_ew: EventWriter<GlobalPlayEvent>,
-) {
-    for (interaction, mut color, mut border_color, children) in &mut interaction_query {
-        let text = match text.get(children[0]) {
-            Ok(text) => text,
-            Err(err) => {
-                info!("Error: {:?}", err);
-                return;
-            }
-        };
-        let button_event = match text.as_str() {
-            "Quit Game" => ButtonClicked::ExitGame,
-            "Start Game" => ButtonClicked::StartGame,
-            "Pause Game" => ButtonClicked::PauseGame,
-            "Resume Game" => ButtonClicked::ResumeGame,
-            "Start Over" => ButtonClicked::RestartGame,
-            err => panic!("Unknown button text {}", err),
-        };
-
-        match *interaction {
-            Interaction::Pressed => {
-                *color = PRESSED_BUTTON.into();
-                border_color.0 = Color::WHITE;
-                event_writter.send(button_event);
-                let event = GlobalPlayEvent::new(AudioFiles::ButtonClickOGG)
-                    .with_settings(PlaybackSettings::ONCE);
-                sfx_play_ew.send(event);
-            }
-            Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
-                border_color.0 = Color::WHITE;
-                commands.spawn((
-                    PlaybackSettings::ONCE,
-                    AudioPlayer::<AudioSource>(asset_server.load("rollover.ogg")),
-                ));
-            }
-            Interaction::None => {
-                *color = NORMAL_BUTTON.into();
-                border_color.0 = Color::BLACK;
-            }
-        }
-    }
+fn setup_game(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
 }
 
-fn button_events(
-    mut event: EventReader<ButtonClicked>,
-    mut next_state: ResMut<NextState<GameState>>,
-    main_menu: Query<Entity, With<MainMenu>>,
-    game_over_menu: Query<Entity, With<GameOverMenu>>,
-    mut commands: Commands,
-) {
-    for button in event.read() {
-        match button {
-            ButtonClicked::StartGame => {
-                next_state.set(GameState::Playing);
-
-                match main_menu.get_single() {
-                    Ok(main_menu) => {
-                        commands.entity(main_menu).despawn_recursive();
-                    }
-                    Err(err) => {
-                        info!("Error: {:?}", err);
-                    }
-                };
-            }
-            ButtonClicked::ExitGame => {
-                std::process::exit(0);
-            }
-            ButtonClicked::PauseGame => {
-                next_state.set(GameState::Paused);
-            }
-            ButtonClicked::ResumeGame => {
-                next_state.set(GameState::Playing);
-            }
-            ButtonClicked::RestartGame => {
-                for game_over_menu in game_over_menu.iter() {
-                    commands.entity(game_over_menu).despawn_recursive();
-                }
-
-                next_state.set(GameState::Start);
-            }
-        }
+// Stops the simulation outright, so bodies keep their velocity but don't move.
+fn freeze_physics(mut config: Query<&mut RapierConfiguration>) {
+    for mut config in config.iter_mut() {
+        config.physics_pipeline_active = false;
     }
 }
 
-fn update_score_text(
-    mut text_query: Query<&mut Text, With<ScoreText>>,
-    player_query: Query<&Player>,
-) {
-    for player in player_query.iter() {
-        for mut text in text_query.iter_mut() {
-            text.0 = format!("Stars: {}", player.stars);
-        }
+fn resume_physics(mut config: Query<&mut RapierConfiguration>) {
+    for mut config in config.iter_mut() {
+        config.physics_pipeline_active = true;
     }
 }
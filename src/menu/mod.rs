@@ -0,0 +1,683 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    attract::AttractMode,
+    audio::AudioVolume,
+    button::{spawn_button, ButtonAction, ButtonActionAppExt, ButtonSet, NORMAL_BUTTON},
+    coop::CoopSettings,
+    highscore::{record_run, spawn_high_score_table, HighScores, LastRunRank},
+    input::{
+        ActionState, BindingsChanged, InputBindings, KeyBinding, PadBinding, RebindTarget,
+        StartRebind,
+    },
+    score::Score,
+    shake::MotionSettings,
+    slider::{spawn_slider, Slider, SliderSet},
+    AppState, GameState,
+};
+
+// EVENTS -------------------------------------
+
+#[derive(Event, Clone, Copy)]
+enum ButtonClicked {
+    ExitGame,
+    StartGame,
+    PauseGame,
+    ResumeGame,
+    RestartGame,
+    QuitToMenu,
+    ShowControls,
+    ShowSettings,
+    BackToTitle,
+    CyclePlayers,
+    ToggleSharedLives,
+}
+
+// COMPONENTS -------------------------------------
+
+#[derive(Component)]
+struct StartButton;
+
+/// Title screen buttons that change `CoopSettings`. Their label shows the
+/// current choice.
+#[derive(Component, Clone, Copy)]
+enum CoopButton {
+    Players,
+    Lives,
+}
+
+impl CoopButton {
+    fn label(self, coop: &CoopSettings) -> String {
+        match self {
+            CoopButton::Players => format!("Players: {}", coop.players),
+            CoopButton::Lives if coop.shared_lives => "Lives: Shared".to_string(),
+            CoopButton::Lives => "Lives: Separate".to_string(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct MainMenu;
+
+#[derive(Component)]
+struct GameOverMenu;
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component)]
+struct ControlsMenu;
+
+#[derive(Component)]
+struct SettingsMenu;
+
+#[derive(Component)]
+struct RebindButton(RebindTarget);
+
+/// Saved settings the menu screens show.
+#[derive(SystemParam)]
+struct MenuSettings<'w> {
+    volume: Res<'w, AudioVolume>,
+    motion: Res<'w, MotionSettings>,
+    coop: Res<'w, CoopSettings>,
+}
+
+/// A slider on the settings screen, bound to one level of a saved setting.
+trait SettingSlider: Component + Copy {
+    type Setting: Resource + Clone;
+
+    const ALL: &'static [Self];
+
+    fn label(self) -> &'static str;
+
+    fn level(self, setting: &mut Self::Setting) -> &mut f32;
+
+    fn save(setting: &Self::Setting);
+}
+
+#[derive(Component, Clone, Copy)]
+enum VolumeSlider {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl SettingSlider for VolumeSlider {
+    type Setting = AudioVolume;
+
+    const ALL: &'static [Self] = &[VolumeSlider::Master, VolumeSlider::Music, VolumeSlider::Sfx];
+
+    fn label(self) -> &'static str {
+        match self {
+            VolumeSlider::Master => "Master",
+            VolumeSlider::Music => "Music",
+            VolumeSlider::Sfx => "Effects",
+        }
+    }
+
+    fn level(self, volume: &mut AudioVolume) -> &mut f32 {
+        match self {
+            VolumeSlider::Master => &mut volume.master,
+            VolumeSlider::Music => &mut volume.music,
+            VolumeSlider::Sfx => &mut volume.sfx,
+        }
+    }
+
+    fn save(volume: &AudioVolume) {
+        volume.save();
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+enum MotionSlider {
+    Shake,
+    HitStop,
+}
+
+impl SettingSlider for MotionSlider {
+    type Setting = MotionSettings;
+
+    const ALL: &'static [Self] = &[MotionSlider::Shake, MotionSlider::HitStop];
+
+    fn label(self) -> &'static str {
+        match self {
+            MotionSlider::Shake => "Screen shake",
+            MotionSlider::HitStop => "Hit-stop",
+        }
+    }
+
+    fn level(self, motion: &mut MotionSettings) -> &mut f32 {
+        match self {
+            MotionSlider::Shake => &mut motion.shake,
+            MotionSlider::HitStop => &mut motion.hit_stop,
+        }
+    }
+
+    fn save(motion: &MotionSettings) {
+        motion.save();
+    }
+}
+
+/// Any screen of the main menu.
+type TitleScreen = Or<(With<MainMenu>, With<ControlsMenu>, With<SettingsMenu>)>;
+
+// SYSTEMS -------------------------------------
+
+fn spawn_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scores: Res<HighScores>,
+    coop: Res<CoopSettings>,
+) {
+    start_menu(&mut commands, &asset_server, &scores, &coop);
+}
+
+fn start_menu(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    scores: &HighScores,
+    coop: &CoopSettings,
+) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Relative,
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            MainMenu,
+            StateScoped(AppState::MainMenu),
+        ))
+        .with_children(|parent| {
+            // Image
+            parent
+                .spawn((Node {
+                    width: Val::Auto,
+                    height: Val::Auto,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },))
+                .with_child((
+                    ImageNode {
+                        image: asset_server.load("title.png"),
+                        ..ImageNode::default()
+                    },
+                    Transform::from_scale(Vec3::splat(0.7)), // Scale down the image
+                ));
+
+            spawn_button(parent, "Start Game", ButtonClicked::StartGame).insert(StartButton);
+            spawn_button(
+                parent,
+                CoopButton::Players.label(coop),
+                ButtonClicked::CyclePlayers,
+            )
+            .insert(CoopButton::Players);
+            spawn_button(
+                parent,
+                CoopButton::Lives.label(coop),
+                ButtonClicked::ToggleSharedLives,
+            )
+            .insert(CoopButton::Lives);
+            spawn_button(parent, "Settings", ButtonClicked::ShowSettings);
+            spawn_button(parent, "Controls", ButtonClicked::ShowControls);
+            spawn_button(parent, "Quit Game", ButtonClicked::ExitGame);
+
+            spawn_high_score_table(parent, scores, None);
+        });
+}
+
+fn controls_menu(commands: &mut Commands, bindings: &InputBindings) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Relative,
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            ControlsMenu,
+            StateScoped(AppState::MainMenu),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Click a control, then press the key or button to use.\nBackspace or Select cancels; a key already in use swaps places."),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            ));
+
+            // Two columns of bindings
+            parent
+                .spawn(Node {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::auto(2),
+                    row_gap: Val::Px(5.0),
+                    column_gap: Val::Px(5.0),
+                    ..default()
+                })
+                .with_children(|grid| {
+                    let targets = KeyBinding::ALL
+                        .into_iter()
+                        .map(RebindTarget::Key)
+                        .chain(PadBinding::ALL.into_iter().map(RebindTarget::Pad));
+                    for target in targets {
+                        grid.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(8.)),
+                                border: UiRect::all(Val::Px(3.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BorderColor(Color::BLACK),
+                            BorderRadius::MAX,
+                            BackgroundColor(NORMAL_BUTTON),
+                            ButtonAction(StartRebind(target)),
+                            RebindButton(target),
+                        ))
+                        .with_child((
+                            Text::new(rebind_label(bindings, target)),
+                            TextFont {
+                                font_size: 20.0,
+                                ..default()
+                            },
+                            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                        ));
+                    }
+                });
+
+            spawn_button(parent, "Back", ButtonClicked::BackToTitle);
+        });
+}
+
+fn settings_menu(commands: &mut Commands, volume: &AudioVolume, motion: &MotionSettings) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Relative,
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            SettingsMenu,
+            StateScoped(AppState::MainMenu),
+        ))
+        .with_children(|parent| {
+            spawn_setting_sliders::<VolumeSlider>(parent, "Volume", volume);
+            // 0 turns either effect off.
+            spawn_setting_sliders::<MotionSlider>(parent, "Motion", motion);
+            spawn_button(parent, "Back", ButtonClicked::BackToTitle);
+        });
+}
+
+// A heading, then a label and slider per level.
+fn spawn_setting_sliders<S: SettingSlider>(
+    parent: &mut ChildBuilder,
+    heading: &str,
+    setting: &S::Setting,
+) {
+    parent.spawn((
+        Text::new(heading),
+        TextFont {
+            font_size: 33.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+    ));
+
+    parent
+        .spawn(Node {
+            display: Display::Grid,
+            grid_template_columns: RepeatedGridTrack::auto(2),
+            row_gap: Val::Px(10.0),
+            column_gap: Val::Px(10.0),
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|grid| {
+            let mut setting = setting.clone();
+            for &slider in S::ALL {
+                grid.spawn((
+                    Text::new(slider.label()),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ));
+                spawn_slider(grid, *slider.level(&mut setting)).insert(slider);
+            }
+        });
+}
+
+fn rebind_label(bindings: &InputBindings, target: RebindTarget) -> String {
+    format!("{}: {}", target.label(), bindings.describe(target))
+}
+
+fn game_over_menu(
+    mut commands: Commands,
+    scores: Res<HighScores>,
+    last_rank: Res<LastRunRank>,
+    score: Res<Score>,
+) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Relative,
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.),
+                margin: UiRect {
+                    left: Val::Auto,
+                    right: Val::Auto,
+                    top: Val::Auto,
+                    bottom: Val::Auto,
+                },
+                padding: UiRect::all(Val::Px(10.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::hsla(1., 1., 1., 0.8)),
+            GameOverMenu,
+            StateScoped(GameState::GameOver),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("Score: {}", score.points)),
+                TextFont {
+                    font_size: 33.0,
+                    ..default()
+                },
+                TextColor(Color::BLACK),
+            ));
+            spawn_high_score_table(parent, &scores, last_rank.0);
+            spawn_button(parent, "Quit Game", ButtonClicked::ExitGame);
+            spawn_button(parent, "Start Over", ButtonClicked::RestartGame);
+            spawn_button(parent, "Main Menu", ButtonClicked::QuitToMenu);
+        });
+}
+
+fn pause_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+            PauseMenu,
+            StateScoped(GameState::Paused),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Paused"),
+                TextFont {
+                    font_size: 48.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            ));
+
+            spawn_button(parent, "Resume Game", ButtonClicked::ResumeGame);
+            spawn_button(parent, "Start Over", ButtonClicked::RestartGame);
+            spawn_button(parent, "Main Menu", ButtonClicked::QuitToMenu);
+            spawn_button(parent, "Quit Game", ButtonClicked::ExitGame);
+        });
+}
+
+fn toggle_pause(
+    actions: Res<ActionState>,
+    state: Res<State<GameState>>,
+    mut event_writter: EventWriter<ButtonClicked>,
+) {
+    if !actions.pause {
+        return;
+    }
+    match state.get() {
+        GameState::Playing => {
+            event_writter.send(ButtonClicked::PauseGame);
+        }
+        GameState::Paused => {
+            event_writter.send(ButtonClicked::ResumeGame);
+        }
+        _ => {}
+    }
+}
+
+fn button_events(
+    mut event: EventReader<ButtonClicked>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for button in event.read() {
+        match button {
+            ButtonClicked::StartGame => {
+                next_app_state.set(AppState::InGame);
+            }
+            ButtonClicked::ExitGame => {
+                exit.send(AppExit::Success);
+            }
+            ButtonClicked::PauseGame => {
+                next_state.set(GameState::Paused);
+            }
+            ButtonClicked::ResumeGame => {
+                next_state.set(GameState::Playing);
+            }
+            ButtonClicked::RestartGame => {
+                next_state.set(GameState::Start);
+            }
+            ButtonClicked::QuitToMenu => {
+                next_app_state.set(AppState::MainMenu);
+            }
+            ButtonClicked::ShowControls
+            | ButtonClicked::ShowSettings
+            | ButtonClicked::BackToTitle
+            | ButtonClicked::CyclePlayers
+            | ButtonClicked::ToggleSharedLives => {}
+        }
+    }
+}
+
+// Swaps between the screens of the main menu without leaving it.
+fn menu_navigation(
+    mut event: EventReader<ButtonClicked>,
+    menus: Query<Entity, TitleScreen>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    bindings: Res<InputBindings>,
+    settings: MenuSettings,
+    scores: Res<HighScores>,
+) {
+    // The screen spawned for one click isn't in `menus` yet, so only the last
+    // click of the frame is acted on, or its screen would be left behind.
+    let Some(button) = event
+        .read()
+        .filter(|button| {
+            matches!(
+                button,
+                ButtonClicked::ShowControls
+                    | ButtonClicked::ShowSettings
+                    | ButtonClicked::BackToTitle
+            )
+        })
+        .last()
+    else {
+        return;
+    };
+    for menu in menus.iter() {
+        commands.entity(menu).despawn_recursive();
+    }
+    match button {
+        ButtonClicked::ShowControls => controls_menu(&mut commands, &bindings),
+        ButtonClicked::ShowSettings => {
+            settings_menu(&mut commands, &settings.volume, &settings.motion)
+        }
+        _ => start_menu(&mut commands, &asset_server, &scores, &settings.coop),
+    }
+}
+
+fn change_coop_settings(mut event: EventReader<ButtonClicked>, mut coop: ResMut<CoopSettings>) {
+    for button in event.read() {
+        match button {
+            ButtonClicked::CyclePlayers => coop.cycle_players(),
+            ButtonClicked::ToggleSharedLives => coop.shared_lives = !coop.shared_lives,
+            _ => continue,
+        }
+        coop.save();
+    }
+}
+
+fn update_coop_labels(
+    coop: Res<CoopSettings>,
+    buttons: Query<(&CoopButton, &Children)>,
+    mut text: Query<&mut Text>,
+) {
+    for (button, children) in buttons.iter() {
+        if let Ok(mut text) = text.get_mut(children[0]) {
+            text.0 = button.label(&coop);
+        }
+    }
+}
+
+fn apply_setting_sliders<S: SettingSlider>(
+    sliders: Query<(&Slider, &S), Changed<Slider>>,
+    mut setting: ResMut<S::Setting>,
+) {
+    for (slider, kind) in sliders.iter() {
+        *kind.level(&mut setting) = slider.value;
+    }
+}
+
+// Saves once a drag is let go rather than on every step of it.
+fn save_setting<S: SettingSlider>(
+    setting: Res<S::Setting>,
+    sliders: Query<&Interaction, With<S>>,
+    mut unsaved: Local<bool>,
+) {
+    if setting.is_changed() && !setting.is_added() {
+        *unsaved = true;
+    }
+    if *unsaved
+        && !sliders
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        S::save(&setting);
+        *unsaved = false;
+    }
+}
+
+fn show_pending_rebind(
+    mut events: EventReader<StartRebind>,
+    buttons: Query<(&RebindButton, &Children)>,
+    mut text: Query<&mut Text>,
+) {
+    for StartRebind(target) in events.read() {
+        for (button, children) in buttons.iter() {
+            if button.0 != *target {
+                continue;
+            }
+            if let Ok(mut text) = text.get_mut(children[0]) {
+                text.0 = format!("{}: ...", target.label());
+            }
+        }
+    }
+}
+
+fn update_rebind_labels(
+    mut events: EventReader<BindingsChanged>,
+    bindings: Res<InputBindings>,
+    buttons: Query<(&RebindButton, &Children)>,
+    mut text: Query<&mut Text>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    for (button, children) in buttons.iter() {
+        if let Ok(mut text) = text.get_mut(children[0]) {
+            text.0 = rebind_label(&bindings, button.0);
+        }
+    }
+}
+
+// PLUGIN -------------------------------------
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_button_action::<ButtonClicked>()
+            .register_button_action::<StartRebind>()
+            .add_systems(OnEnter(AppState::MainMenu), spawn_main_menu)
+            .add_systems(OnEnter(GameState::Paused), pause_menu)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                game_over_menu
+                    .after(record_run)
+                    .run_if(not(resource_exists::<AttractMode>)),
+            )
+            .add_systems(
+                Update,
+                toggle_pause
+                    .run_if(in_state(AppState::InGame))
+                    .before(button_events),
+            )
+            .add_systems(
+                Update,
+                (
+                    button_events,
+                    menu_navigation,
+                    show_pending_rebind,
+                    update_rebind_labels,
+                    change_coop_settings,
+                    update_coop_labels.run_if(resource_changed::<CoopSettings>),
+                )
+                    .chain()
+                    .after(ButtonSet),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        apply_setting_sliders::<VolumeSlider>,
+                        save_setting::<VolumeSlider>,
+                    )
+                        .chain(),
+                    (
+                        apply_setting_sliders::<MotionSlider>,
+                        save_setting::<MotionSlider>,
+                    )
+                        .chain(),
+                )
+                    .after(SliderSet),
+            );
+    }
+}
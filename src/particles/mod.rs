@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    holes::{HoleCollapsed, HoleDestroyed},
+    player::{PlayerCollectedStar, PlayerCollidedHole},
+    AppState, GameState,
+};
+
+// BURSTS -------------------------------------
+
+/// What a burst of particles looks like. Each particle flies off in a random
+/// direction and fades from `start_color` to `end_color` over its lifetime.
+#[derive(Clone, Copy, Debug)]
+pub struct Burst {
+    pub count: u32,
+    /// Particles leave at up to this speed, in pixels per second.
+    pub speed: f32,
+    pub lifetime_secs: f32,
+    pub size: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+}
+
+pub const HOLE_EXPLOSION: Burst = Burst {
+    count: 40,
+    speed: 300.0,
+    lifetime_secs: 0.6,
+    size: 6.0,
+    start_color: Color::srgb(1.0, 0.8, 0.3),
+    end_color: Color::srgba(0.8, 0.1, 0.0, 0.0),
+};
+
+pub const STAR_PICKUP: Burst = Burst {
+    count: 16,
+    speed: 150.0,
+    lifetime_secs: 0.4,
+    size: 4.0,
+    start_color: Color::srgb(1.0, 1.0, 0.6),
+    end_color: Color::srgba(1.0, 1.0, 1.0, 0.0),
+};
+
+pub const PLAYER_HIT: Burst = Burst {
+    count: 30,
+    speed: 250.0,
+    lifetime_secs: 0.8,
+    size: 5.0,
+    start_color: Color::srgb(0.4, 0.6, 1.0),
+    end_color: Color::srgba(0.1, 0.1, 0.5, 0.0),
+};
+
+pub const HOLE_COLLAPSE: Burst = Burst {
+    count: 60,
+    speed: 400.0,
+    lifetime_secs: 1.0,
+    size: 6.0,
+    start_color: Color::srgb(0.5, 0.9, 1.0),
+    end_color: Color::srgba(0.2, 0.0, 0.6, 0.0),
+};
+
+// EVENTS -------------------------------------
+
+/// Spawns `burst` at `position`.
+#[derive(Event)]
+pub struct SpawnBurst {
+    pub position: Vec2,
+    pub burst: Burst,
+}
+
+// COMPONENTS -------------------------------------
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    lifetime: Timer,
+    start_color: Color,
+    end_color: Color,
+}
+
+// SYSTEMS -------------------------------------
+
+// Particles are only for show, so they use their own randomness and leave
+// `GameRng`, and with it replays, alone.
+fn spawn_bursts(mut commands: Commands, mut events: EventReader<SpawnBurst>) {
+    let mut rng = rand::thread_rng();
+    for SpawnBurst { position, burst } in events.read() {
+        for _ in 0..burst.count {
+            let direction = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+            let speed = burst.speed * rng.gen_range(0.3..1.0);
+            commands.spawn((
+                Sprite {
+                    color: burst.start_color,
+                    custom_size: Some(Vec2::splat(burst.size)),
+                    ..default()
+                },
+                Transform::from_translation(position.extend(2.0)),
+                Particle {
+                    velocity: direction * speed,
+                    lifetime: Timer::from_seconds(burst.lifetime_secs, TimerMode::Once),
+                    start_color: burst.start_color,
+                    end_color: burst.end_color,
+                },
+                StateScoped(AppState::InGame),
+            ));
+        }
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (particle.velocity * time.delta_secs()).extend(0.0);
+        sprite.color = particle
+            .start_color
+            .mix(&particle.end_color, particle.lifetime.fraction());
+    }
+}
+
+fn burst_on_hole_destroyed(
+    mut events: EventReader<HoleDestroyed>,
+    mut bursts: EventWriter<SpawnBurst>,
+) {
+    for hole in events.read() {
+        bursts.send(SpawnBurst {
+            position: hole.position,
+            burst: HOLE_EXPLOSION,
+        });
+    }
+}
+
+fn burst_on_star_collected(
+    mut events: EventReader<PlayerCollectedStar>,
+    mut bursts: EventWriter<SpawnBurst>,
+) {
+    for star in events.read() {
+        bursts.send(SpawnBurst {
+            position: star.position,
+            burst: STAR_PICKUP,
+        });
+    }
+}
+
+fn burst_on_player_hit(
+    mut events: EventReader<PlayerCollidedHole>,
+    mut bursts: EventWriter<SpawnBurst>,
+) {
+    for hit in events.read() {
+        bursts.send(SpawnBurst {
+            position: hit.position,
+            burst: PLAYER_HIT,
+        });
+    }
+}
+
+fn burst_on_hole_collapsed(
+    mut events: EventReader<HoleCollapsed>,
+    holes: Query<&Transform>,
+    mut bursts: EventWriter<SpawnBurst>,
+) {
+    for collapse in events.read() {
+        if let Ok(transform) = holes.get(collapse.hole) {
+            bursts.send(SpawnBurst {
+                position: transform.translation.truncate(),
+                burst: HOLE_COLLAPSE,
+            });
+        }
+    }
+}
+
+// PLUGIN -------------------------------------
+
+/// Sprite particles for explosions, pickups and hits. Send `SpawnBurst` for
+/// anything else that needs one.
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnBurst>().add_systems(
+            Update,
+            (
+                (
+                    burst_on_hole_destroyed,
+                    burst_on_star_collected,
+                    burst_on_player_hit,
+                    burst_on_hole_collapsed,
+                ),
+                spawn_bursts,
+                update_particles.run_if(in_state(GameState::Playing)),
+            )
+                .chain()
+                .run_if(in_state(AppState::InGame)),
+        );
+    }
+}
@@ -1,24 +1,64 @@
-use bevy::prelude::*;
-use bevy_audio_controller::prelude::{AudioFiles, GlobalPlayEvent};
+use bevy::{prelude::*, utils::HashSet};
+use bevy_audio_controller::prelude::{AudioFiles, PlayEvent};
 use bevy_rapier2d::prelude::*;
 
-use crate::{holes::Hole, star::Star, GameState, Health};
+use crate::{
+    arena::{
+        started_with, ArenaBounds, ArenaWall, KeepInArena, HOLE_GROUP, HUD_HEIGHT, MISSILE_GROUP,
+        PLAYER_GROUP, WALL_GROUP,
+    },
+    audio::SfxChannel,
+    controller::{Intent, RunController},
+    coop::{CoopSettings, MAX_PLAYERS},
+    holes::Hole,
+    powerup::{Magnet, Multishot, Shield, SpeedBoost},
+    star::{Star, StarKind},
+    tuning::{PlayerTuning, Tuning},
+    AppState, GameState,
+};
+
+/// Tint of each player's ball and HUD panel. Player one keeps the ball as
+/// drawn.
+pub const PLAYER_COLORS: [Color; MAX_PLAYERS] = [
+    Color::WHITE,
+    Color::srgb(0.4, 1.0, 0.4),
+    Color::srgb(1.0, 0.5, 1.0),
+    Color::srgb(1.0, 1.0, 0.3),
+];
+
+/// Gap between co-op players when a run starts.
+const PLAYER_SPACING: f32 = 120.0;
 
 /// EVENTS -------------------------------------
+/// A hole hit `player` at `position`.
 #[derive(Event)]
-pub struct PlayerCollidedHole;
+pub struct PlayerCollidedHole {
+    pub player: Entity,
+    pub position: Vec2,
+}
 #[derive(Event)]
-pub struct PlayerCollectedStar;
+pub struct PlayerCollectedStar {
+    pub player: Entity,
+    pub position: Vec2,
+    pub kind: StarKind,
+    pub value: u32,
+}
 
 #[derive(Event)]
-pub struct PlayerAddLife;
+pub struct PlayerAddLife {
+    pub player: Entity,
+}
 
 #[derive(Event)]
-pub struct PlayerAddMissiles;
+pub struct PlayerAddMissiles {
+    pub player: Entity,
+}
 
 /// COMPONENTS -------------------------------------
 #[derive(Component, Copy, Clone)]
 pub struct Player {
+    /// 0 for player one.
+    pub slot: usize,
     pub life: i8,
     pub stars: u32,
     pub speed: f32,
@@ -26,15 +66,46 @@ pub struct Player {
 }
 
 #[derive(Component)]
-pub struct Missile;
+pub struct Missile {
+    /// The player who fired it.
+    pub shooter: Entity,
+    /// Despawns the missile when it finishes.
+    pub lifetime: Timer,
+    /// Wall bounces left.
+    pub ricochets: u32,
+}
+
+/// Holes pass through the player while this lasts. The sprite blinks.
+#[derive(Component)]
+pub struct Invulnerable {
+    pub timer: Timer,
+}
 
-impl Default for Player {
-    fn default() -> Self {
+/// A player with no lives left. They stay in the world, hidden and out of
+/// reach, so their stars still count, until the rest are out too.
+#[derive(Component)]
+pub struct Out;
+
+/// Players a hole can hit right now.
+type Hittable = (With<Player>, Without<Invulnerable>);
+
+/// What `player_movement` reads and steers.
+type Steering<'a> = (
+    &'a mut Velocity,
+    &'a Player,
+    &'a Intent,
+    &'a mut Transform,
+    Has<SpeedBoost>,
+);
+
+impl Player {
+    pub fn from_tuning(tuning: &PlayerTuning, slot: usize) -> Self {
         Player {
-            life: 3,
+            slot,
+            life: tuning.life,
             stars: 0,
-            speed: 750.0,
-            missiles: 500.0,
+            speed: tuning.speed,
+            missiles: tuning.missiles,
         }
     }
 }
@@ -44,190 +115,196 @@ impl Default for Player {
 fn setup_player(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    tuning: Res<Tuning>,
+    coop: Res<CoopSettings>,
+    run_controller: RunController,
     player: Query<Entity, With<Player>>,
+    missiles: Query<Entity, With<Missile>>,
 ) {
-    for player in player.iter() {
-        commands.entity(player).despawn_recursive();
+    for entity in player.iter().chain(missiles.iter()) {
+        commands.entity(entity).despawn_recursive();
     }
 
-    commands.spawn((
-        Sprite::from_image(asset_server.load("ball_blue_large.png")),
-        Transform::from_xyz(0., 0., 1.),
-        Player::default(),
-        RigidBody::Dynamic,
-        Collider::ball(32.0),
-        GravityScale(0.),
-        Velocity::zero(),
-    ));
-}
-
-fn gamepad_movement(
-    gamepads: Query<&Gamepad>,
-    mut query: Query<(&mut Velocity, &Player, &mut Transform), With<Player>>,
-    time: Res<Time>,
-) {
-    for (mut velocity, player, mut transform) in query.iter_mut() {
-        for gamepad in &gamepads {
-            let speed = player.speed;
-            let mut direction = Vec2::ZERO;
-
-            // Get stick input
-            if let (Some(left_stick_x), Some(left_stick_y)) = (
-                gamepad.get(GamepadAxis::LeftStickX),
-                gamepad.get(GamepadAxis::LeftStickY),
-            ) {
-                direction = Vec2::new(left_stick_x, left_stick_y);
-
-                // Only rotate if stick has significant movement
-                if direction.length() > 0.1 {
-                    let angle = direction.y.atan2(direction.x) - std::f32::consts::FRAC_PI_2;
-                    transform.rotation = Quat::from_rotation_z(angle);
-                }
-            }
-
-            // Normalize direction for movement
-            if direction != Vec2::ZERO {
-                direction = direction.normalize();
-            }
-
-            // Apply movement
-            let target_velocity = direction * speed;
-            let acceleration = 5.0;
-            velocity.linvel = velocity
-                .linvel
-                .lerp(target_velocity, acceleration * time.delta_secs());
-        }
-    }
-}
-
-fn fire_missile_keyboard(
-    keys: Res<ButtonInput<KeyCode>>,
-    query: Single<(&mut Transform, &mut Player), With<Player>>,
-    asset_server: Res<AssetServer>,
-    mut commands: Commands,
-    mut sfx_play_ew: EventWriter<GlobalPlayEvent>,
-) {
-    let transform = &query.0;
-    let mut player = *query.1;
-    // Determine missile firing parameters.
-    // Use the player's current rotation to compute the firing direction.
-    let missile_speed = 1000.0;
-
-    // default fire direction to player direction from transform
-    let forward = transform.rotation * Vec3::Y;
-    let fire_direction = Vec2::new(forward.x, forward.y);
-
-    if fire_direction != Vec2::ZERO && player.missiles > 0.0 && keys.just_pressed(KeyCode::Space) {
-        // Offset the spawn position slightly so the missile doesn't overlap the player.
-        let spawn_position = transform.translation + (fire_direction.extend(0.0) * 50.0);
-        let angle = fire_direction.y.atan2(fire_direction.x) - std::f32::consts::FRAC_PI_2;
+    let controllers = run_controller.controllers();
+    let players = controllers.len();
+    // A shared pool holds everyone's lives.
+    let life = if coop.shared_lives {
+        tuning.player.life * players as i8
+    } else {
+        tuning.player.life
+    };
+    for (slot, controller) in controllers.into_iter().enumerate() {
+        // Side by side, centred on the middle of the arena.
+        let x = (slot as f32 - (players - 1) as f32 / 2.0) * PLAYER_SPACING;
         commands.spawn((
-            Sprite::from_image(asset_server.load("ball_red_small.png")),
-            Transform {
-                translation: spawn_position,
-                rotation: Quat::from_rotation_z(angle),
-                ..Default::default()
+            Sprite {
+                image: asset_server.load("ball_blue_large.png"),
+                color: PLAYER_COLORS[slot],
+                ..default()
             },
+            Transform::from_xyz(x, 0., 1.),
+            Player {
+                life,
+                ..Player::from_tuning(&tuning.player, slot)
+            },
+            controller,
+            Intent::default(),
             RigidBody::Dynamic,
+            Collider::ball(32.0),
+            CollisionGroups::new(PLAYER_GROUP, Group::ALL),
+            KeepInArena { radius: 32.0 },
             GravityScale(0.),
-            Velocity {
-                linvel: fire_direction * missile_speed,
-                angvel: 0.0,
-            },
-            Missile,
+            Velocity::zero(),
+            // Gravity wells pull through these.
+            ExternalForce::default(),
+            ReadMassProperties::default(),
+            StateScoped(AppState::InGame),
         ));
-        player.missiles -= 1.0;
-        let event = GlobalPlayEvent::new(AudioFiles::PopOGG).with_settings(PlaybackSettings::ONCE);
-        sfx_play_ew.send(event);
     }
 }
 
 fn fire_missile(
-    gamepads: Query<&Gamepad>,
-    keys: Res<ButtonInput<KeyCode>>,
-    query: Single<(&mut Transform, &mut Player), With<Player>>,
+    tuning: Res<Tuning>,
+    mut players: Query<(Entity, &Transform, &mut Player, &Intent), Without<Out>>,
+    multishot: Query<(), With<Multishot>>,
     asset_server: Res<AssetServer>,
     mut commands: Commands,
-    mut sfx_play_ew: EventWriter<GlobalPlayEvent>,
+    mut sfx_play_ew: EventWriter<PlayEvent<SfxChannel>>,
 ) {
-    for gamepad in &gamepads {
-        let transform = &query.0;
-        let mut player = *query.1;
+    for (shooter, transform, mut player, intent) in players.iter_mut() {
         // Determine missile firing parameters.
-        // Use the player's current rotation to compute the firing direction.
-        let missile_speed = 1000.0;
+        let missile_speed = tuning.missiles.speed;
 
-        // default fire direction to player direction from transform
+        // Fire where the player aims, or the way the player is facing.
         let forward = transform.rotation * Vec3::Y;
-        let mut fire_direction = Vec2::new(forward.x, forward.y);
-
-        if let (Some(stick_x), Some(stick_y)) = (
-            gamepad.get(GamepadAxis::LeftStickX),
-            gamepad.get(GamepadAxis::LeftStickY),
-        ) {
-            let stick_direction = Vec2::new(stick_x, stick_y);
-            if stick_direction.length() > 0.1 {
-                fire_direction = stick_direction.normalize();
+        let fire_direction = intent
+            .aim
+            .unwrap_or_else(|| Vec2::new(forward.x, forward.y));
+
+        if fire_direction != Vec2::ZERO && player.missiles > 0.0 && intent.fire {
+            // A spread shot fires two more missiles either side of the aim.
+            let angles = if multishot.contains(shooter) {
+                let spread = tuning.power_ups.spread_degrees.to_radians();
+                vec![-spread, 0.0, spread]
+            } else {
+                vec![0.0]
+            };
+            for offset in angles {
+                let direction = Vec2::from_angle(offset).rotate(fire_direction);
+                // Offset the spawn position slightly so the missile doesn't overlap the player.
+                let spawn_position = transform.translation + (direction.extend(0.0) * 50.0);
+                let angle = direction.y.atan2(direction.x) - std::f32::consts::FRAC_PI_2;
+                commands.spawn((
+                    Sprite::from_image(asset_server.load("ball_red_small.png")),
+                    Transform {
+                        translation: spawn_position,
+                        rotation: Quat::from_rotation_z(angle),
+                        ..Default::default()
+                    },
+                    RigidBody::Dynamic,
+                    GravityScale(0.),
+                    Velocity {
+                        linvel: direction * missile_speed,
+                        angvel: 0.0,
+                    },
+                    Collider::ball(16.0),
+                    CollisionGroups::new(MISSILE_GROUP, WALL_GROUP | HOLE_GROUP),
+                    // Bounce off walls without losing speed.
+                    Restitution {
+                        coefficient: 1.0,
+                        combine_rule: CoefficientCombineRule::Max,
+                    },
+                    Friction {
+                        coefficient: 0.0,
+                        combine_rule: CoefficientCombineRule::Min,
+                    },
+                    Ccd::enabled(),
+                    ActiveEvents::COLLISION_EVENTS,
+                    ExternalForce::default(),
+                    ReadMassProperties::default(),
+                    Missile {
+                        shooter,
+                        lifetime: Timer::from_seconds(
+                            tuning.missiles.lifetime_secs,
+                            TimerMode::Once,
+                        ),
+                        ricochets: tuning.missiles.ricochets,
+                    },
+                    StateScoped(AppState::InGame),
+                ));
             }
-        }
-
-        if fire_direction != Vec2::ZERO
-            && player.missiles > 0.0
-            && (gamepad.just_pressed(GamepadButton::South) || keys.just_pressed(KeyCode::Space))
-        {
-            // Offset the spawn position slightly so the missile doesn't overlap the player.
-            let spawn_position = transform.translation + (fire_direction.extend(0.0) * 50.0);
-            let angle = fire_direction.y.atan2(fire_direction.x) - std::f32::consts::FRAC_PI_2;
-            commands.spawn((
-                Sprite::from_image(asset_server.load("ball_red_small.png")),
-                Transform {
-                    translation: spawn_position,
-                    rotation: Quat::from_rotation_z(angle),
-                    ..Default::default()
-                },
-                RigidBody::Dynamic,
-                GravityScale(0.),
-                Velocity {
-                    linvel: fire_direction * missile_speed,
-                    angvel: 0.0,
-                },
-                Missile,
-            ));
             player.missiles -= 1.0;
-            let event =
-                GlobalPlayEvent::new(AudioFiles::PopOGG).with_settings(PlaybackSettings::ONCE);
+            let event = PlayEvent::new(AudioFiles::PopOGG).with_settings(PlaybackSettings::ONCE);
             sfx_play_ew.send(event);
         }
     }
 }
 
-fn player_movement(
-    mut query: Query<(&mut Velocity, &Player, &mut Transform), With<Player>>,
-    keys: Res<ButtonInput<KeyCode>>,
+fn expire_missiles(
+    mut commands: Commands,
     time: Res<Time>,
+    mut missiles: Query<(Entity, &mut Missile)>,
 ) {
-    for (mut velocity, player, mut transform) in query.iter_mut() {
-        let speed = player.speed;
-
-        let mut direction = Vec2::ZERO;
-        if keys.pressed(KeyCode::KeyW) {
-            direction.y += 1.0;
+    for (entity, mut missile) in missiles.iter_mut() {
+        if missile.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
         }
-        if keys.pressed(KeyCode::KeyS) {
-            direction.y -= 1.0;
+    }
+}
+
+// Catches missiles that got past the walls, e.g. fired from right against one.
+fn cull_missiles(
+    mut commands: Commands,
+    bounds: Res<ArenaBounds>,
+    missiles: Query<(Entity, &Transform), With<Missile>>,
+) {
+    const MARGIN: f32 = 50.0;
+    for (entity, transform) in missiles.iter() {
+        let position = transform.translation;
+        if position.x.abs() > bounds.half_width() + MARGIN
+            || position.y.abs() > bounds.half_height() + MARGIN
+        {
+            commands.entity(entity).despawn_recursive();
         }
-        if keys.pressed(KeyCode::KeyA) {
-            direction.x -= 1.0;
+    }
+}
+
+fn missile_ricochet(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    walls: Query<(), With<ArenaWall>>,
+    mut missiles: Query<&mut Missile>,
+) {
+    for collision in collisions.read() {
+        let Some((_, missile)) = started_with(collision, |entity| walls.contains(entity)) else {
+            continue;
+        };
+        let Ok(mut state) = missiles.get_mut(missile) else {
+            continue;
+        };
+        if state.ricochets == 0 {
+            commands.entity(missile).despawn_recursive();
+        } else {
+            state.ricochets -= 1;
         }
-        if keys.pressed(KeyCode::KeyD) {
-            direction.x += 1.0;
+    }
+}
+
+fn player_movement(mut query: Query<Steering, Without<Out>>, tuning: Res<Tuning>, time: Res<Time>) {
+    for (mut velocity, player, intent, mut transform, boosted) in query.iter_mut() {
+        let mut speed = player.speed;
+        if boosted {
+            speed *= tuning.power_ups.speed_multiplier;
         }
-        // Normalize so diagonal movement isn’t faster.
-        if direction != Vec2::ZERO {
-            direction = direction.normalize();
+
+        let mut direction = intent.movement;
+        // Only rotate on significant movement, and normalize so a half-tilted
+        // stick or a diagonal isn’t slower or faster.
+        if direction.length() > 0.1 {
             let angle = direction.y.atan2(direction.x) - std::f32::consts::FRAC_PI_2;
             transform.rotation = Quat::from_rotation_z(angle);
         }
+        direction = direction.normalize_or_zero();
 
         // Option 2: Smoothly interpolate toward the desired velocity (for gradual acceleration)
         let target_velocity = direction * speed;
@@ -240,104 +317,220 @@ fn player_movement(
 
 fn star_collision_event(
     mut commands: Commands,
-    player: Query<&Transform, With<Player>>,
-    stars: Query<(Entity, &Transform), With<Star>>,
+    mut collisions: EventReader<CollisionEvent>,
+    player: Query<(), With<Player>>,
+    stars: Query<(&Star, &Transform)>,
     mut events: EventWriter<PlayerCollectedStar>,
 ) {
-    for player_transform in player.iter() {
-        for (star_entity, star_transform) in stars.iter() {
-            let delta = player_transform.translation - star_transform.translation;
-            // Player half size is 32 and star half size is 16
-            if delta.x.abs() < 48.0 && delta.y.abs() < 48.0 {
-                commands.entity(star_entity).despawn();
-                events.send(PlayerCollectedStar);
-            }
+    // The star is only despawned after this system, so without this two
+    // players touching it in the same tick would both collect it.
+    let mut collected = HashSet::new();
+    for collision in collisions.read() {
+        let Some((star, other)) = started_with(collision, |entity| stars.contains(entity)) else {
+            continue;
+        };
+        if player.contains(other) && collected.insert(star) {
+            let Ok((&Star { kind, value }, transform)) = stars.get(star) else {
+                continue;
+            };
+            commands.entity(star).despawn();
+            events.send(PlayerCollectedStar {
+                player: other,
+                position: transform.translation.truncate(),
+                kind,
+                value,
+            });
         }
     }
 }
 
 fn hole_collision_event(
     mut commands: Commands,
-    player: Query<&Transform, With<Player>>,
-    holes: Query<(Entity, &Transform), With<Hole>>,
+    mut collisions: EventReader<CollisionEvent>,
+    player: Query<(&Transform, Has<Shield>), Hittable>,
+    holes: Query<(), With<Hole>>,
     mut events: EventWriter<PlayerCollidedHole>,
 ) {
-    for player_transform in player.iter() {
-        for (hole_entity, hole_transform) in holes.iter() {
-            let delta = player_transform.translation - hole_transform.translation;
-            //PLayer half size is 32 and hole half size is 40
-            if delta.x.abs() < 48.0 && delta.y.abs() < 48.0 {
-                // Adjust collision radius as needed
-                commands.entity(hole_entity).despawn();
-                events.send(PlayerCollidedHole);
-            }
+    // `Invulnerable` only lands after this tick, so a second hole touching the
+    // same player now is let through the same as one touching it later.
+    let mut hit = HashSet::new();
+    for collision in collisions.read() {
+        let Some((hole, other)) = started_with(collision, |entity| holes.contains(entity)) else {
+            continue;
+        };
+        let Ok((transform, shielded)) = player.get(other) else {
+            continue;
+        };
+        if !hit.insert(other) {
+            continue;
+        }
+        commands.entity(hole).despawn();
+        if shielded {
+            // The shield takes the hit instead.
+            commands.entity(other).remove::<Shield>();
+        } else {
+            events.send(PlayerCollidedHole {
+                player: other,
+                position: transform.translation.truncate(),
+            });
         }
     }
 }
+
 fn handle_star_collection(
-    mut player: Query<&mut Player>,
+    mut players: Query<&mut Player>,
+    tuning: Res<Tuning>,
     mut events: EventReader<PlayerCollectedStar>,
-    mut sfx_play_ew: EventWriter<GlobalPlayEvent>,
+    mut sfx_play_ew: EventWriter<PlayEvent<SfxChannel>>,
     mut life_event: EventWriter<PlayerAddLife>,
     mut missile_event: EventWriter<PlayerAddMissiles>,
 ) {
-    for _ in events.read() {
-        for mut player in player.iter_mut() {
-            player.stars += 1;
-            let event =
-                GlobalPlayEvent::new(AudioFiles::PowerUp2OGG).with_settings(PlaybackSettings::ONCE);
-            sfx_play_ew.send(event);
-            if player.stars % 10 == 0 {
-                player.speed = player.speed * 1.1;
-                life_event.send(PlayerAddLife);
-                missile_event.send(PlayerAddMissiles);
-            }
+    let every_stars = tuning.rewards.every_stars.max(1);
+    for star in events.read() {
+        let Ok(mut player) = players.get_mut(star.player) else {
+            continue;
+        };
+        let before = player.stars;
+        player.stars += star.value;
+        let event = PlayEvent::new(AudioFiles::PowerUp2OGG).with_settings(PlaybackSettings::ONCE);
+        sfx_play_ew.send(event);
+        // Stars worth more than one can step over a multiple.
+        if before / every_stars != player.stars / every_stars {
+            player.speed *= tuning.rewards.speed_multiplier;
+            life_event.send(PlayerAddLife {
+                player: star.player,
+            });
+            missile_event.send(PlayerAddMissiles {
+                player: star.player,
+            });
         }
     }
 }
 
 fn handle_player_collided_with_hole_event(
-    mut player: Query<(&mut Player, &mut Transform), With<Player>>,
+    mut commands: Commands,
+    tuning: Res<Tuning>,
+    coop: Res<CoopSettings>,
+    bounds: Res<ArenaBounds>,
+    mut players: Query<(Entity, &mut Player, &mut Transform, &mut Velocity), Without<Out>>,
+    holes: Query<&Transform, (With<Hole>, Without<Player>)>,
     mut events: EventReader<PlayerCollidedHole>,
 ) {
-    for _ in events.read() {
-        for (mut player, mut transform) in player.iter_mut() {
+    for hit in events.read() {
+        for (entity, mut player, mut transform, mut velocity) in players.iter_mut() {
+            // With shared lives every hit comes out of everyone's pool.
+            if entity != hit.player && !coop.shared_lives {
+                continue;
+            }
             player.life -= 1;
-            transform.translation = Vec3::new(0., 0., 0.);
+            if player.life <= 0 {
+                knock_out(&mut commands, entity, &mut velocity);
+                continue;
+            }
+            if entity != hit.player {
+                continue;
+            }
+            let holes: Vec<Vec2> = holes
+                .iter()
+                .map(|hole| hole.translation.truncate())
+                .collect();
+            let spawn = safe_respawn_point(&bounds, &holes);
+            transform.translation = spawn.extend(transform.translation.z);
+            *velocity = Velocity::zero();
+            commands.entity(entity).insert(Invulnerable {
+                timer: Timer::from_seconds(tuning.player.invulnerable_secs, TimerMode::Once),
+            });
         }
     }
 }
 
-fn handle_player_add_life(
-    mut players: Query<&mut Player>,
-    mut events: EventReader<PlayerAddLife>,
-    health: Query<(Entity, &Transform), With<Health>>,
-    window: Query<&Window>,
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-) {
-    for _ in events.read() {
-        for mut player in players.iter_mut() {
-            player.life += 1;
-            println!("Player Lives: {}", player.life);
-            let window = window.single();
-            for health in health.iter() {
-                commands.entity(health.0).despawn();
+// Takes a player out of play for the rest of the run.
+fn knock_out(commands: &mut Commands, entity: Entity, velocity: &mut Velocity) {
+    *velocity = Velocity::zero();
+    commands
+        .entity(entity)
+        .insert((Out, Visibility::Hidden))
+        .remove::<(
+            Collider,
+            Invulnerable,
+            Shield,
+            Multishot,
+            SpeedBoost,
+            Magnet,
+        )>();
+}
+
+/// The point on a grid over the arena that is farthest from every hole.
+fn safe_respawn_point(bounds: &ArenaBounds, holes: &[Vec2]) -> Vec2 {
+    const STEPS: usize = 16;
+    const PLAYER_RADIUS: f32 = 32.0;
+    let min = Vec2::new(
+        -bounds.half_width() + PLAYER_RADIUS,
+        -bounds.half_height() + PLAYER_RADIUS,
+    );
+    let max = Vec2::new(
+        bounds.half_width() - PLAYER_RADIUS,
+        bounds.half_height() - HUD_HEIGHT - PLAYER_RADIUS,
+    )
+    .max(min);
+
+    let mut best = Vec2::ZERO;
+    let mut best_distance = f32::MIN;
+    for i in 0..=STEPS {
+        for j in 0..=STEPS {
+            let candidate = min + (max - min) * Vec2::new(i as f32, j as f32) / STEPS as f32;
+            let distance = holes
+                .iter()
+                .map(|hole| hole.distance(candidate))
+                .fold(f32::MAX, f32::min);
+            if distance > best_distance {
+                best_distance = distance;
+                best = candidate;
             }
+        }
+    }
+    best
+}
 
-            // Spawn health indicators
-            for i in 0..player.life {
-                let position = Vec3::new(
-                    window.width() / 2.0 - 20.0 - (i as f32 * 35.0),
-                    window.height() / 2.0 - 20.0,
-                    0.,
-                );
+// Holes are left out of the player's collision groups during the i-frames, so
+// a hole still on the player when they end starts a new contact and hits.
+fn blink_invulnerable(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut players: Query<(
+        Entity,
+        &mut Invulnerable,
+        &mut Visibility,
+        &mut CollisionGroups,
+    )>,
+) {
+    const BLINK_SECS: f32 = 0.1;
+    for (entity, mut invulnerable, mut visibility, mut groups) in players.iter_mut() {
+        if invulnerable.timer.tick(time.delta()).finished() {
+            *visibility = Visibility::Inherited;
+            groups.filters = Group::ALL;
+            commands.entity(entity).remove::<Invulnerable>();
+            continue;
+        }
+        groups.set_if_neq(CollisionGroups::new(PLAYER_GROUP, Group::ALL - HOLE_GROUP));
+        let blink = (invulnerable.timer.elapsed_secs() / BLINK_SECS) as u32;
+        *visibility = if blink.is_multiple_of(2) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
 
-                commands.spawn((
-                    Sprite::from_image(asset_server.load("ball_blue_small.png")),
-                    Transform::from_translation(position),
-                    Health,
-                ));
+fn handle_player_add_life(
+    coop: Res<CoopSettings>,
+    mut players: Query<(Entity, &mut Player), Without<Out>>,
+    mut events: EventReader<PlayerAddLife>,
+) {
+    for reward in events.read() {
+        for (entity, mut player) in players.iter_mut() {
+            if entity == reward.player || coop.shared_lives {
+                player.life += 1;
             }
         }
     }
@@ -345,54 +538,30 @@ fn handle_player_add_life(
 
 fn handle_player_add_missiles(
     mut players: Query<&mut Player>,
+    tuning: Res<Tuning>,
     mut events: EventReader<PlayerAddMissiles>,
 ) {
-    for _ in events.read() {
-        for mut player in players.iter_mut() {
-            player.missiles += 10.0;
+    for reward in events.read() {
+        if let Ok(mut player) = players.get_mut(reward.player) {
+            player.missiles += tuning.rewards.missiles;
         }
     }
 }
 
-fn handle_player_update_health_event(
-    player: Query<&Player>,
-    health: Query<(Entity, &Transform), With<Health>>,
+// The run is over once everyone is out.
+fn check_game_over(
+    players: Query<&Player>,
     mut events: EventReader<PlayerCollidedHole>,
-    window: Query<&Window>,
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
     mut next_state: ResMut<NextState<GameState>>,
-    mut sfx_play_ew: EventWriter<GlobalPlayEvent>,
+    mut sfx_play_ew: EventWriter<PlayEvent<SfxChannel>>,
 ) {
     for _ in events.read() {
-        for player in player.iter() {
-            if player.life == 0 {
-                next_state.set(GameState::GameOver);
-            } else {
-                let event = GlobalPlayEvent::new(AudioFiles::ExplosionCrunch001OGG)
-                    .with_settings(PlaybackSettings::ONCE);
-                sfx_play_ew.send(event);
-                let window = window.single();
-
-                for health in health.iter() {
-                    commands.entity(health.0).despawn();
-                }
-
-                // Spawn health indicators
-                for i in 0..player.life {
-                    let position = Vec3::new(
-                        window.width() / 2.0 - 20.0 - (i as f32 * 35.0),
-                        window.height() / 2.0 - 20.0,
-                        0.,
-                    );
-
-                    commands.spawn((
-                        Sprite::from_image(asset_server.load("ball_blue_small.png")),
-                        Transform::from_translation(position),
-                        Health,
-                    ));
-                }
-            }
+        if players.iter().all(|player| player.life <= 0) {
+            next_state.set(GameState::GameOver);
+        } else {
+            let event = PlayEvent::new(AudioFiles::ExplosionCrunch001OGG)
+                .with_settings(PlaybackSettings::ONCE);
+            sfx_play_ew.send(event);
         }
     }
 }
@@ -406,24 +575,70 @@ impl Plugin for PlayerPlugin {
             .add_systems(
                 FixedUpdate,
                 (
-                    gamepad_movement,
-                    fire_missile_keyboard,
                     fire_missile,
                     player_movement,
                     handle_star_collection,
                     hole_collision_event,
                     star_collision_event,
                     handle_player_collided_with_hole_event,
-                    handle_player_update_health_event,
+                    check_game_over,
                     handle_player_add_life,
                     handle_player_add_missiles,
                 )
                     .chain()
                     .run_if(in_state(GameState::Playing)),
             )
+            .add_systems(
+                FixedUpdate,
+                (
+                    expire_missiles,
+                    cull_missiles,
+                    missile_ricochet,
+                    blink_invulnerable,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
             .add_event::<PlayerCollectedStar>()
             .add_event::<PlayerCollidedHole>()
             .add_event::<PlayerAddMissiles>()
             .add_event::<PlayerAddLife>();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn respawn_is_far_from_a_hole() {
+        let bounds = ArenaBounds::default();
+        let hole = Vec2::new(-500.0, -250.0);
+        let spawn = safe_respawn_point(&bounds, &[hole]);
+        assert!(spawn.x > 0.0 && spawn.y > 0.0, "{spawn}");
+    }
+
+    #[test]
+    fn respawn_stays_inside_the_arena() {
+        let bounds = ArenaBounds::default();
+        let holes = [
+            Vec2::ZERO,
+            Vec2::new(300.0, 100.0),
+            Vec2::new(-300.0, -100.0),
+        ];
+        let spawn = safe_respawn_point(&bounds, &holes);
+        assert!(spawn.x.abs() <= bounds.half_width());
+        assert!(spawn.y >= -bounds.half_height());
+        assert!(spawn.y <= bounds.half_height() - HUD_HEIGHT);
+    }
+
+    #[test]
+    fn respawn_without_holes_is_in_the_arena() {
+        let bounds = ArenaBounds {
+            width: 200.0,
+            height: 100.0,
+        };
+        let spawn = safe_respawn_point(&bounds, &[]);
+        assert!(spawn.x.abs() <= bounds.half_width());
+        assert!(spawn.y.abs() <= bounds.half_height());
+    }
+}
@@ -0,0 +1,191 @@
+use std::ops::DerefMut;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    player::{Player, PlayerCollectedStar},
+    rng::{GameRng, RngSet},
+    star::{Star, StarKind},
+    tuning::Tuning,
+    GameState,
+};
+
+// COMPONENTS -------------------------------------
+
+/// A timed effect on a `Player`. Gameplay systems check for the component,
+/// so removing it when the timer runs out reverts the effect.
+pub trait PowerUp: Component + DerefMut<Target = Timer> {
+    const LABEL: &'static str;
+}
+
+/// Absorbs the next hole hit, then goes away.
+#[derive(Component, Deref, DerefMut)]
+pub struct Shield(Timer);
+
+/// Every shot fires three missiles in a spread.
+#[derive(Component, Deref, DerefMut)]
+pub struct Multishot(Timer);
+
+/// Multiplies the player's speed.
+#[derive(Component, Deref, DerefMut)]
+pub struct SpeedBoost(Timer);
+
+/// Pulls nearby stars towards the player.
+#[derive(Component, Deref, DerefMut)]
+pub struct Magnet(Timer);
+
+impl PowerUp for Shield {
+    const LABEL: &'static str = "Shield";
+}
+
+impl PowerUp for Multishot {
+    const LABEL: &'static str = "Spread shot";
+}
+
+impl PowerUp for SpeedBoost {
+    const LABEL: &'static str = "Speed";
+}
+
+impl PowerUp for Magnet {
+    const LABEL: &'static str = "Magnet";
+}
+
+/// Power-ups handed out by power-up stars, picked at random.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PowerUpKind {
+    Shield,
+    Multishot,
+    SpeedBoost,
+    Magnet,
+}
+
+impl PowerUpKind {
+    const ALL: [PowerUpKind; 4] = [
+        PowerUpKind::Shield,
+        PowerUpKind::Multishot,
+        PowerUpKind::SpeedBoost,
+        PowerUpKind::Magnet,
+    ];
+}
+
+/// Every power-up a `Player` can have running.
+pub type ActivePowerUps<'a> = (
+    Option<&'a Shield>,
+    Option<&'a Multishot>,
+    Option<&'a SpeedBoost>,
+    Option<&'a Magnet>,
+);
+
+// SYSTEMS -------------------------------------
+
+fn grant_power_ups(
+    mut commands: Commands,
+    tuning: Res<Tuning>,
+    mut rng: ResMut<GameRng>,
+    mut events: EventReader<PlayerCollectedStar>,
+) {
+    for star in events.read() {
+        if star.kind != StarKind::PowerUp {
+            continue;
+        }
+        let timer = Timer::from_seconds(tuning.power_ups.secs, TimerMode::Once);
+        let kind = PowerUpKind::ALL[rng.gen_range(0..PowerUpKind::ALL.len())];
+        // Picking up one that is already running starts it over.
+        let mut player = commands.entity(star.player);
+        match kind {
+            PowerUpKind::Shield => player.insert(Shield(timer)),
+            PowerUpKind::Multishot => player.insert(Multishot(timer)),
+            PowerUpKind::SpeedBoost => player.insert(SpeedBoost(timer)),
+            PowerUpKind::Magnet => player.insert(Magnet(timer)),
+        };
+    }
+}
+
+fn expire_power_up<T: PowerUp>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut players: Query<(Entity, &mut T)>,
+) {
+    for (entity, mut power_up) in players.iter_mut() {
+        if power_up.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<T>();
+        }
+    }
+}
+
+fn pull_stars(
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    players: Query<&Transform, (With<Player>, With<Magnet>)>,
+    mut stars: Query<&mut Transform, (With<Star>, Without<Player>)>,
+) {
+    let radius = tuning.power_ups.magnet_radius;
+    for player in players.iter() {
+        let target = player.translation.truncate();
+        for mut star in stars.iter_mut() {
+            let offset = target - star.translation.truncate();
+            if offset.length() < radius {
+                let step =
+                    offset.clamp_length_max(tuning.power_ups.magnet_speed * time.delta_secs());
+                star.translation += step.extend(0.0);
+            }
+        }
+    }
+}
+
+// UI -------------------------------------
+
+fn countdown<T: PowerUp>(power_up: Option<&T>) -> Option<String> {
+    power_up.map(|power_up| {
+        let secs = power_up.remaining_secs().ceil();
+        format!("{} {secs}s", T::LABEL)
+    })
+}
+
+/// A countdown like "Shield 3s" for each power-up that is running.
+pub fn countdowns((shield, multishot, speed, magnet): ActivePowerUps) -> Vec<String> {
+    [
+        countdown(shield),
+        countdown(multishot),
+        countdown(speed),
+        countdown(magnet),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+// PLUGIN -------------------------------------
+
+pub trait PowerUpAppExt {
+    /// Ticks `T` on every player and removes it when it runs out.
+    fn register_power_up<T: PowerUp>(&mut self) -> &mut Self;
+}
+
+impl PowerUpAppExt for App {
+    fn register_power_up<T: PowerUp>(&mut self) -> &mut Self {
+        self.add_systems(
+            FixedUpdate,
+            expire_power_up::<T>.run_if(in_state(GameState::Playing)),
+        )
+    }
+}
+
+/// Timed power-ups granted by power-up stars. Each player's panel in the HUD
+/// counts theirs down.
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_power_up::<Shield>()
+            .register_power_up::<Multishot>()
+            .register_power_up::<SpeedBoost>()
+            .register_power_up::<Magnet>()
+            .add_systems(
+                FixedUpdate,
+                (grant_power_ups.in_set(RngSet::PowerUps), pull_stars)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::{ArenaBounds, PinnedArenaBounds},
+    attract::AttractMode,
+    cli::CliArgs,
+    controller::{ControllerSet, Intent, ReplayedIntents},
+    coop::CoopSettings,
+    player::Player,
+    rng::RngSeed,
+    AppState, GameState,
+};
+
+/// Bumped whenever `Intent` or `Replay` change shape.
+const REPLAY_VERSION: u32 = 4;
+
+// REPLAY -------------------------------------
+
+/// One run: the seed, arena and co-op setup it started from, and what every
+/// player did in each `FixedUpdate` tick spent in `GameState::Playing`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    /// Spawns and respawns depend on the arena size, so playback uses this
+    /// instead of the window.
+    pub bounds: ArenaBounds,
+    /// `players` is how many were spawned, which is fewer than were picked
+    /// when there weren't enough gamepads.
+    pub coop: CoopSettings,
+    /// One `Intent` per player per tick, player one first.
+    pub frames: Vec<Vec<Intent>>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let replay: Replay = ron::from_str(&text).map_err(|err| err.to_string())?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "replay version {} is not supported (expected {})",
+                replay.version, REPLAY_VERSION
+            ));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        fs::write(path, text).map_err(|err| err.to_string())
+    }
+}
+
+// RESOURCES -------------------------------------
+
+#[derive(Resource)]
+struct ReplayRecorder {
+    path: PathBuf,
+    replay: Replay,
+}
+
+#[derive(Resource)]
+struct ReplayPlayback {
+    replay: Replay,
+    cursor: usize,
+}
+
+// SYSTEMS -------------------------------------
+
+// The arena keeps its size for the whole recorded run, since resizes are
+// not part of the replay.
+fn start_recording(
+    mut commands: Commands,
+    seed: Res<RngSeed>,
+    bounds: Res<ArenaBounds>,
+    coop: Res<CoopSettings>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    recorder.replay = Replay {
+        version: REPLAY_VERSION,
+        seed: seed.0,
+        bounds: *bounds,
+        coop: *coop,
+        frames: Vec::new(),
+    };
+    commands.insert_resource(PinnedArenaBounds);
+}
+
+fn unpin_arena(mut commands: Commands) {
+    commands.remove_resource::<PinnedArenaBounds>();
+}
+
+// Records what the players did rather than what the devices said, so a bot
+// run plays back the same without the bot.
+fn record_input(players: Query<(&Player, &Intent)>, mut recorder: ResMut<ReplayRecorder>) {
+    let mut players: Vec<_> = players.iter().collect();
+    players.sort_by_key(|(player, _)| player.slot);
+    let frame: Vec<Intent> = players.into_iter().map(|(_, intent)| *intent).collect();
+    recorder.replay.coop.players = frame.len();
+    recorder.replay.frames.push(frame);
+}
+
+fn save_recording(recorder: Res<ReplayRecorder>) {
+    if recorder.replay.frames.is_empty() {
+        return;
+    }
+    match recorder.replay.save(&recorder.path) {
+        Ok(()) => info!(
+            "Saved replay with {} ticks to {}",
+            recorder.replay.frames.len(),
+            recorder.path.display()
+        ),
+        Err(err) => error!("Failed to save replay: {}", err),
+    }
+}
+
+fn save_recording_on_exit(exit: EventReader<AppExit>, recorder: Res<ReplayRecorder>) {
+    if !exit.is_empty() {
+        save_recording(recorder);
+    }
+}
+
+fn rewind_playback(mut playback: ResMut<ReplayPlayback>) {
+    playback.cursor = 0;
+}
+
+fn play_input(mut replayed: ResMut<ReplayedIntents>, mut playback: ResMut<ReplayPlayback>) {
+    let cursor = playback.cursor;
+    replayed.0 = match playback.replay.frames.get(cursor) {
+        Some(frame) => frame.clone(),
+        None => {
+            if cursor == playback.replay.frames.len() {
+                info!("Replay finished after {} ticks", cursor);
+            }
+            Vec::new()
+        }
+    };
+    playback.cursor += 1;
+}
+
+// PLUGIN -------------------------------------
+
+/// Records the run to the file given with `--record`, or feeds the run back
+/// from the file given with `--replay`. Playback also takes over the seed,
+/// the arena size and the co-op setup, and the viewer can still pause.
+/// Demo runs are left alone.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let cli = app
+            .world()
+            .get_resource::<CliArgs>()
+            .cloned()
+            .unwrap_or_default();
+
+        if let Some(path) = cli.replay {
+            match Replay::load(&path) {
+                Ok(replay) => {
+                    info!(
+                        "Playing back {} ticks from {}",
+                        replay.frames.len(),
+                        path.display()
+                    );
+                    app.insert_resource(RngSeed(replay.seed))
+                        .insert_resource(replay.bounds)
+                        .insert_resource(PinnedArenaBounds)
+                        .insert_resource(replay.coop)
+                        .init_resource::<ReplayedIntents>()
+                        .insert_resource(ReplayPlayback { replay, cursor: 0 })
+                        .add_systems(OnEnter(GameState::Start), rewind_playback)
+                        .add_systems(
+                            FixedPreUpdate,
+                            play_input.before(ControllerSet).run_if(
+                                in_state(GameState::Playing)
+                                    .and(not(resource_exists::<AttractMode>)),
+                            ),
+                        );
+                }
+                Err(err) => error!("Failed to load replay {}: {}", path.display(), err),
+            }
+        } else if let Some(path) = cli.record {
+            app.insert_resource(ReplayRecorder {
+                path,
+                replay: Replay::default(),
+            })
+            .add_systems(
+                OnEnter(GameState::Start),
+                start_recording.run_if(not(resource_exists::<AttractMode>)),
+            )
+            .add_systems(
+                FixedPreUpdate,
+                record_input
+                    .after(ControllerSet)
+                    .run_if(in_state(GameState::Playing).and(not(resource_exists::<AttractMode>))),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                save_recording.run_if(not(resource_exists::<AttractMode>)),
+            )
+            .add_systems(OnExit(AppState::InGame), unpin_arena)
+            .add_systems(Last, save_recording_on_exit);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::GameState;
+
+/// Environment variable read when no seed is given on the command line.
+const SEED_ENV_VAR: &str = "ROLLY_POLLY_SEED";
+
+/// Every `FixedUpdate` system that draws from `GameRng` goes in one of these.
+/// They run in this order, so timers that fire on the same tick draw in the
+/// same order every time.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RngSet {
+    Stars,
+    Holes,
+    PowerUps,
+}
+
+// RESOURCES -------------------------------------
+
+/// Seed used for every run. Insert it before adding `RngPlugin` to pin it,
+/// otherwise it is read from `ROLLY_POLLY_SEED` or picked at random.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RngSeed(pub u64);
+
+impl Default for RngSeed {
+    fn default() -> Self {
+        let seed = std::env::var(SEED_ENV_VAR)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(rand::random);
+        RngSeed(seed)
+    }
+}
+
+/// The only source of randomness for gameplay. It is reseeded from
+/// `RngSeed` at the start of every run, so the same seed and the same
+/// inputs always produce the same stars and holes.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng(ChaCha8Rng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+// SYSTEMS -------------------------------------
+
+fn reseed_rng(seed: Res<RngSeed>, mut rng: ResMut<GameRng>) {
+    info!("RNG seed: {}", seed.0);
+    *rng = GameRng::from_seed(seed.0);
+}
+
+// PLUGIN -------------------------------------
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RngSeed>();
+        let seed = app.world().resource::<RngSeed>().0;
+        app.insert_resource(GameRng::from_seed(seed))
+            .add_systems(OnEnter(GameState::Start), reseed_rng)
+            .configure_sets(
+                FixedUpdate,
+                (RngSet::Stars, RngSet::Holes, RngSet::PowerUps).chain(),
+            );
+    }
+}
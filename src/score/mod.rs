@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::{
+    holes::HoleDestroyed,
+    player::{Player, PlayerCollectedStar},
+    tuning::Tuning,
+    GameState,
+};
+
+// RESOURCES -------------------------------------
+
+/// Points for the current run. This is what the high score table ranks by.
+#[derive(Resource, Debug)]
+pub struct Score {
+    pub points: u32,
+    /// Multiplier on every point scored. Quick pickups raise it and it drops
+    /// back by one each time the combo timer runs out.
+    pub combo: u32,
+    /// Running while another pickup would raise the combo.
+    combo_timer: Option<Timer>,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Score {
+            points: 0,
+            combo: 1,
+            combo_timer: None,
+        }
+    }
+}
+
+impl Score {
+    fn add(&mut self, points: f32) {
+        self.points += (points * self.combo as f32).round() as u32;
+    }
+}
+
+// SYSTEMS -------------------------------------
+
+fn reset_score(mut score: ResMut<Score>) {
+    *score = Score::default();
+}
+
+fn score_stars(
+    tuning: Res<Tuning>,
+    mut score: ResMut<Score>,
+    mut events: EventReader<PlayerCollectedStar>,
+) {
+    let scoring = &tuning.score;
+    for star in events.read() {
+        if score.combo_timer.is_some() {
+            score.combo = (score.combo + 1).min(scoring.max_combo.max(1));
+        }
+        score.combo_timer = Some(Timer::from_seconds(scoring.combo_secs, TimerMode::Once));
+        score.add((scoring.star_points * star.value) as f32);
+    }
+}
+
+// Holes shot from farther away are worth more.
+fn score_holes(
+    tuning: Res<Tuning>,
+    mut score: ResMut<Score>,
+    players: Query<&Transform, With<Player>>,
+    mut events: EventReader<HoleDestroyed>,
+) {
+    let scoring = &tuning.score;
+    for hole in events.read() {
+        let distance = players.get(hole.shooter).map_or(0.0, |shooter| {
+            shooter.translation.truncate().distance(hole.position)
+        });
+        let bonus = 1.0 + distance / scoring.hole_distance_scale.max(1.0);
+        score.add(scoring.hole_points as f32 * bonus);
+    }
+}
+
+fn decay_combo(time: Res<Time>, mut score: ResMut<Score>) {
+    let score = &mut *score;
+    let Some(timer) = score.combo_timer.as_mut() else {
+        return;
+    };
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+    if score.combo > 1 {
+        score.combo -= 1;
+        timer.reset();
+    } else {
+        score.combo_timer = None;
+    }
+}
+
+// PLUGIN -------------------------------------
+
+/// Points from stars and destroyed holes, with a combo multiplier for quick
+/// pickups.
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .add_systems(OnEnter(GameState::Start), reset_score)
+            .add_systems(
+                FixedUpdate,
+                (decay_combo, score_stars, score_holes)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    holes::{HoleCollapsed, HoleDestroyed},
+    player::PlayerCollidedHole,
+    storage, GameState,
+};
+
+const MOTION_KEY: &str = "motion";
+
+/// Camera offset in pixels and roll in radians at full trauma.
+const MAX_OFFSET: f32 = 12.0;
+const MAX_ROLL: f32 = 0.05;
+/// Trauma lost per second.
+const TRAUMA_DECAY: f32 = 1.5;
+/// How fast the game runs during a hit-stop.
+const HIT_STOP_SPEED: f32 = 0.1;
+
+// RESOURCES -------------------------------------
+
+/// Screen shake and hit-stop strength from the settings screen, each from
+/// 0 (off) to 1.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MotionSettings {
+    pub shake: f32,
+    pub hit_stop: f32,
+}
+
+impl Default for MotionSettings {
+    fn default() -> Self {
+        MotionSettings {
+            shake: 1.0,
+            hit_stop: 1.0,
+        }
+    }
+}
+
+impl MotionSettings {
+    pub fn save(&self) {
+        storage::save(MOTION_KEY, self);
+    }
+}
+
+/// How hard the camera shakes, from 0 to 1. The shake grows with the square
+/// of it, so small knocks stay subtle.
+#[derive(Resource, Default, Debug)]
+pub struct Trauma(f32);
+
+impl Trauma {
+    pub fn add(&mut self, amount: f32) {
+        self.0 = (self.0 + amount).min(1.0);
+    }
+}
+
+/// Slows the game down for a moment after a big impact. Runs on real time.
+#[derive(Resource, Default, Debug)]
+pub struct HitStop(Option<Timer>);
+
+impl HitStop {
+    /// Keeps the longer of any hit-stop already running and this one.
+    pub fn start(&mut self, secs: f32) {
+        let remaining = self.0.as_ref().map_or(0.0, Timer::remaining_secs);
+        if secs > remaining {
+            self.0 = Some(Timer::from_seconds(secs, TimerMode::Once));
+        }
+    }
+}
+
+// SYSTEMS -------------------------------------
+
+fn shake_on_impacts(
+    mut trauma: ResMut<Trauma>,
+    mut hit_stop: ResMut<HitStop>,
+    settings: Res<MotionSettings>,
+    mut player_hits: EventReader<PlayerCollidedHole>,
+    mut holes_destroyed: EventReader<HoleDestroyed>,
+    mut holes_collapsed: EventReader<HoleCollapsed>,
+) {
+    for _ in player_hits.read() {
+        trauma.add(0.6);
+        hit_stop.start(0.12 * settings.hit_stop);
+    }
+    for _ in holes_destroyed.read() {
+        trauma.add(0.25);
+        hit_stop.start(0.04 * settings.hit_stop);
+    }
+    for _ in holes_collapsed.read() {
+        trauma.add(0.5);
+        hit_stop.start(0.08 * settings.hit_stop);
+    }
+}
+
+fn shake_camera(
+    time: Res<Time<Real>>,
+    settings: Res<MotionSettings>,
+    mut trauma: ResMut<Trauma>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    let mut rng = rand::thread_rng();
+    let shake = trauma.0 * trauma.0 * settings.shake;
+    for mut transform in cameras.iter_mut() {
+        transform.translation.x = MAX_OFFSET * shake * rng.gen_range(-1.0..1.0);
+        transform.translation.y = MAX_OFFSET * shake * rng.gen_range(-1.0..1.0);
+        transform.rotation = Quat::from_rotation_z(MAX_ROLL * shake * rng.gen_range(-1.0..1.0));
+    }
+    trauma.0 = (trauma.0 - TRAUMA_DECAY * time.delta_secs()).max(0.0);
+}
+
+fn run_hit_stop(
+    real_time: Res<Time<Real>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    let Some(timer) = hit_stop.0.as_mut() else {
+        return;
+    };
+    if timer.tick(real_time.delta()).finished() {
+        hit_stop.0 = None;
+        virtual_time.set_relative_speed(1.0);
+    } else {
+        virtual_time.set_relative_speed(HIT_STOP_SPEED);
+    }
+}
+
+// Leaves the camera still and the clock at full speed for menus and pauses.
+fn settle(
+    mut trauma: ResMut<Trauma>,
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    trauma.0 = 0.0;
+    hit_stop.0 = None;
+    virtual_time.set_relative_speed(1.0);
+    for mut transform in cameras.iter_mut() {
+        transform.translation = Vec3::ZERO.with_z(transform.translation.z);
+        transform.rotation = Quat::IDENTITY;
+    }
+}
+
+// PLUGIN -------------------------------------
+
+/// Trauma-based camera shake and a short hit-stop on impacts. Either can be
+/// turned down or off on the settings screen.
+pub struct ShakePlugin;
+
+impl Plugin for ShakePlugin {
+    fn build(&self, app: &mut App) {
+        let settings = storage::load::<MotionSettings>(MOTION_KEY).unwrap_or_default();
+        app.insert_resource(settings)
+            .init_resource::<Trauma>()
+            .init_resource::<HitStop>()
+            .add_systems(
+                Update,
+                (shake_on_impacts, run_hit_stop, shake_camera)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), settle);
+    }
+}
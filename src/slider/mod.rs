@@ -0,0 +1,94 @@
+use bevy::{ecs::system::EntityCommands, prelude::*, ui::RelativeCursorPosition};
+
+use crate::button::{NORMAL_BUTTON, PRESSED_BUTTON};
+
+// SETS -------------------------------------
+
+/// Dragging updates `Slider::value` here. Systems reacting to
+/// `Changed<Slider>` should run after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SliderSet;
+
+// COMPONENTS -------------------------------------
+
+/// A horizontal slider. `value` runs from 0 at the left edge to 1 at the right.
+#[derive(Component, Debug, PartialEq)]
+pub struct Slider {
+    pub value: f32,
+}
+
+#[derive(Component)]
+struct SliderFill;
+
+// SYSTEMS -------------------------------------
+
+fn drag_slider(mut sliders: Query<(&Interaction, &RelativeCursorPosition, &mut Slider)>) {
+    for (interaction, cursor, mut slider) in sliders.iter_mut() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        // Keeps following the cursor past either end while the press lasts.
+        slider.set_if_neq(Slider {
+            value: position.x.clamp(0.0, 1.0),
+        });
+    }
+}
+
+fn update_slider_fill(
+    sliders: Query<(&Slider, &Children), Changed<Slider>>,
+    mut fills: Query<&mut Node, With<SliderFill>>,
+) {
+    for (slider, children) in sliders.iter() {
+        for child in children.iter() {
+            if let Ok(mut node) = fills.get_mut(*child) {
+                node.width = Val::Percent(slider.value * 100.0);
+            }
+        }
+    }
+}
+
+// SPAWNING -------------------------------------
+
+/// Spawns a slider under `parent`, starting at `value`.
+pub fn spawn_slider<'a>(parent: &'a mut ChildBuilder, value: f32) -> EntityCommands<'a> {
+    let mut slider = parent.spawn((
+        Node {
+            width: Val::Px(300.0),
+            height: Val::Px(24.0),
+            border: UiRect::all(Val::Px(3.0)),
+            ..default()
+        },
+        BorderColor(Color::BLACK),
+        BorderRadius::MAX,
+        BackgroundColor(NORMAL_BUTTON),
+        Interaction::default(),
+        RelativeCursorPosition::default(),
+        Slider { value },
+    ));
+    slider.with_child((
+        Node {
+            width: Val::Percent(value * 100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BorderRadius::MAX,
+        BackgroundColor(PRESSED_BUTTON),
+        SliderFill,
+    ));
+    slider
+}
+
+// PLUGIN -------------------------------------
+pub struct SliderPlugin;
+
+impl Plugin for SliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (drag_slider, update_slider_fill).chain().in_set(SliderSet),
+        );
+    }
+}
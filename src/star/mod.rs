@@ -1,54 +1,120 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use rand::{distributions::WeightedIndex, Rng};
+use serde::{Deserialize, Serialize};
 
-use crate::GameState;
+use crate::{
+    arena::{ArenaBounds, KeepInArena, HOLE_GROUP, PLAYER_GROUP, STAR_GROUP},
+    rng::{GameRng, RngSet},
+    tuning::{StarTuning, Tuning},
+    AppState, GameState,
+};
 
 // EVENTS -------------------------------------
 
 // COMPONENTS -------------------------------------
 
 #[derive(Component)]
-pub struct Star;
+pub struct Star {
+    pub kind: StarKind,
+    /// Stars added to the player's count when collected.
+    pub value: u32,
+}
+
+/// Star variants. Their weight, value and lifetime come from `StarTuning::kinds`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StarKind {
+    Normal,
+    Gold,
+    /// Fades away unless collected in time.
+    Fading,
+    /// Grants a random power-up when collected.
+    PowerUp,
+}
+
+impl StarKind {
+    fn color(self) -> Color {
+        match self {
+            StarKind::Normal => Color::WHITE,
+            StarKind::Gold => Color::srgb(1.0, 0.8, 0.2),
+            StarKind::Fading => Color::srgb(0.7, 0.9, 1.0),
+            StarKind::PowerUp => Color::srgb(0.5, 1.0, 0.5),
+        }
+    }
+}
+
+/// Despawns the star when it finishes. The star fades out as it runs.
+#[derive(Component)]
+struct StarLifetime(Timer);
+
+// RESOURCES -------------------------------------
+
+#[derive(Resource)]
+struct StarSpawnTimer(Timer);
+
+impl Default for StarSpawnTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            StarTuning::default().spawn_secs,
+            TimerMode::Once,
+        ))
+    }
+}
 
 // SYSTEMS -------------------------------------
 
-fn despawn_stars(mut commands: Commands, stars: Query<Entity, With<Star>>) {
+fn despawn_stars(
+    mut commands: Commands,
+    stars: Query<Entity, With<Star>>,
+    mut timer: ResMut<StarSpawnTimer>,
+) {
     for star in stars.iter() {
         commands.entity(star).despawn_recursive();
     }
+    timer.0.reset();
+}
+
+fn tick_star_spawn_timer(time: Res<Time>, tuning: Res<Tuning>, mut timer: ResMut<StarSpawnTimer>) {
+    if tuning.is_changed() {
+        timer
+            .0
+            .set_duration(Duration::from_secs_f32(tuning.stars.spawn_secs));
+    }
+    timer.0.tick(time.delta());
 }
 
 fn spawn_star(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    time: Res<Time>,
-    mut timer: Local<f32>,
+    tuning: Res<Tuning>,
+    mut timer: ResMut<StarSpawnTimer>,
+    mut rng: ResMut<GameRng>,
     // Query for any entity with both Transform and Collider components.
     colliders: Query<&Transform, With<Star>>,
-    windows: Query<&Window>,
+    bounds: Res<ArenaBounds>,
 ) {
-    *timer += time.delta_secs();
-    // Spawn a new star every 2 seconds if there are less than 4 stars.
-    if *timer >= 2.0 {
-        *timer = 0.0;
-        let window = windows.single();
+    // Spawn a new star every few seconds.
+    if timer.0.finished() {
+        timer.0.reset();
         let model = asset_server.load("star.png");
 
         const STAR_RADIUS: f32 = 16.0;
         const MAX_ATTEMPTS: usize = 10;
 
-        // Closure to generate a random candidate position within window bounds,
+        // Closure to generate a random candidate position within arena bounds,
         // ensuring the entire 32x32 star is visible and avoiding the top 56 pixels.
-        let generate_candidate = || -> Vec2 {
+        let mut generate_candidate = || -> Vec2 {
             let half_star = 16.0;
             let margin_top = 56.0;
-            let x_min = -window.width() / 2.0 + half_star;
-            let x_max = window.width() / 2.0 - half_star;
+            let x_min = -bounds.half_width() + half_star;
+            let x_max = bounds.half_width() - half_star;
             // Ensure the star is fully visible and not in the top 56 pixels:
-            let y_min = -window.height() / 2.0 + half_star;
-            let y_max = window.height() / 2.0 - half_star - margin_top;
-            let x = rand::random::<f32>() * (x_max - x_min) + x_min;
-            let y = rand::random::<f32>() * (y_max - y_min) + y_min;
+            let y_min = -bounds.half_height() + half_star;
+            let y_max = bounds.half_height() - half_star - margin_top;
+            let x = rng.gen::<f32>() * (x_max - x_min) + x_min;
+            let y = rng.gen::<f32>() * (y_max - y_min) + y_min;
             Vec2::new(x, y)
         };
 
@@ -81,12 +147,49 @@ fn spawn_star(
             return;
         }
 
-        commands.spawn((
-            Sprite::from_image(model),
-            Star,
+        // Pick a kind weighted by how common it is.
+        let Ok(weights) = WeightedIndex::new(tuning.stars.kinds.iter().map(|kind| kind.weight))
+        else {
+            return;
+        };
+        let kind = &tuning.stars.kinds[rng.sample(&weights)];
+
+        let mut sprite = Sprite::from_image(model);
+        sprite.color = kind.kind.color();
+        let mut star = commands.spawn((
+            sprite,
+            Star {
+                kind: kind.kind,
+                value: kind.value,
+            },
+            KeepInArena {
+                radius: STAR_RADIUS,
+            },
             RigidBody::Fixed,
+            Collider::ball(STAR_RADIUS),
+            Sensor,
+            CollisionGroups::new(STAR_GROUP, PLAYER_GROUP | HOLE_GROUP),
+            ActiveEvents::COLLISION_EVENTS,
             Transform::from_translation(Vec3::new(spawn_position.x, spawn_position.y, 1.0)),
+            StateScoped(AppState::InGame),
         ));
+        if let Some(lifetime) = kind.lifetime_secs {
+            star.insert(StarLifetime(Timer::from_seconds(lifetime, TimerMode::Once)));
+        }
+    }
+}
+
+fn fade_stars(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut stars: Query<(Entity, &mut StarLifetime, &mut Sprite)>,
+) {
+    for (entity, mut lifetime, mut sprite) in stars.iter_mut() {
+        if lifetime.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            sprite.color.set_alpha(lifetime.0.fraction_remaining());
+        }
     }
 }
 
@@ -101,10 +204,16 @@ pub struct StarPlugin;
 
 impl Plugin for StarPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Start), despawn_stars)
+        app.init_resource::<StarSpawnTimer>()
+            .add_systems(OnEnter(GameState::Start), despawn_stars)
             .add_systems(
                 FixedUpdate,
-                (spawn_star, start_pulse)
+                (
+                    tick_star_spawn_timer,
+                    spawn_star.in_set(RngSet::Stars),
+                    fade_stars,
+                    start_pulse,
+                )
                     .chain()
                     .run_if(in_state(GameState::Playing)),
             );
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+const APP_NAME: &str = "rolly_polly";
+
+/// Reads a value written by [`save`], if there is one.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let text = read(key)?;
+    match ron::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("Ignoring unreadable saved {}: {}", key, err);
+            None
+        }
+    }
+}
+
+/// Writes `value` as RON to the user config directory on native builds,
+/// or to the browser's `localStorage` on wasm.
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    let text = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(text) => text,
+        Err(err) => {
+            error!("Failed to serialize {}: {}", key, err);
+            return;
+        }
+    };
+    if let Err(err) = write(key, &text) {
+        error!("Failed to save {}: {}", key, err);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(key: &str) -> Option<std::path::PathBuf> {
+    Some(
+        dirs::config_dir()?
+            .join(APP_NAME)
+            .join(format!("{key}.ron")),
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(key: &str, text: &str) -> Result<(), String> {
+    let path = path(key).ok_or("no config directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    std::fs::write(path, text).map_err(|err| err.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(key: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("{APP_NAME}.{key}"))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(key: &str, text: &str) -> Result<(), String> {
+    local_storage()
+        .ok_or("localStorage is not available")?
+        .set_item(&format!("{APP_NAME}.{key}"), text)
+        .map_err(|err| format!("{err:?}"))
+}
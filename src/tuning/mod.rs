@@ -0,0 +1,453 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, LoadState},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{holes::HoleKind, star::StarKind};
+
+const TUNING_PATH: &str = "game.tuning.ron";
+
+// TUNING -------------------------------------
+
+/// Every balance number in the game, loaded from `assets/game.tuning.ron`.
+///
+/// The file is watched on native builds, so saving it while the game runs
+/// updates this resource in place. Anything left out of the file keeps the
+/// default below.
+#[derive(Asset, TypePath, Resource, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tuning {
+    pub player: PlayerTuning,
+    pub holes: HoleTuning,
+    pub stars: StarTuning,
+    pub missiles: MissileTuning,
+    pub rewards: RewardTuning,
+    pub waves: WaveTuning,
+    pub power_ups: PowerUpTuning,
+    pub score: ScoreTuning,
+}
+
+impl Tuning {
+    pub fn hole_spawn_secs(&self, wave: u32) -> f32 {
+        let scaled = self.holes.spawn_secs * self.waves.spawn_secs_multiplier.powi(wave as i32 - 1);
+        scaled.max(self.waves.min_spawn_secs)
+    }
+
+    pub fn hole_speed(&self, wave: u32) -> f32 {
+        self.holes.speed + self.waves.speed_step * (wave - 1) as f32
+    }
+
+    pub fn max_holes(&self, wave: u32) -> usize {
+        let extra = self.waves.max_holes_step * (wave - 1) as usize;
+        (self.holes.max_holes + extra).min(self.waves.max_holes_limit)
+    }
+
+    /// Hole kinds that can spawn in `wave`.
+    pub fn hole_kinds(&self, wave: u32) -> impl Iterator<Item = &HoleKindTuning> {
+        self.waves
+            .kinds
+            .iter()
+            .filter(move |kind| kind.from_wave <= wave)
+    }
+}
+
+/// Starting stats. These are read when a run starts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerTuning {
+    pub speed: f32,
+    pub missiles: f32,
+    pub life: i8,
+    /// How long the player can't be hit after losing a life.
+    pub invulnerable_secs: f32,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        PlayerTuning {
+            speed: 750.0,
+            missiles: 500.0,
+            life: 3,
+            invulnerable_secs: 2.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HoleTuning {
+    pub speed: f32,
+    pub spawn_secs: f32,
+    /// Holes stop spawning while this many are in play.
+    pub max_holes: usize,
+    /// Pull of a gravity well at its centre, in pixels per second squared.
+    pub well_strength: f32,
+    /// Distance from a gravity well where its pull fades out.
+    pub well_radius: f32,
+    /// Merged holes move this much slower than the pair they came from.
+    pub merge_speed_multiplier: f32,
+    /// Merged holes stop growing at this mass, where they collapse into a
+    /// gravity well. A normal hole has a mass of 1.
+    pub max_merge_mass: f32,
+}
+
+impl Default for HoleTuning {
+    fn default() -> Self {
+        HoleTuning {
+            speed: 200.0,
+            spawn_secs: 3.0,
+            max_holes: 9,
+            well_strength: 1500.0,
+            well_radius: 250.0,
+            merge_speed_multiplier: 0.8,
+            max_merge_mass: 4.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StarTuning {
+    pub spawn_secs: f32,
+    pub kinds: Vec<StarKindTuning>,
+}
+
+impl Default for StarTuning {
+    fn default() -> Self {
+        StarTuning {
+            spawn_secs: 2.0,
+            kinds: vec![
+                StarKindTuning {
+                    kind: StarKind::Normal,
+                    weight: 10,
+                    value: 1,
+                    lifetime_secs: None,
+                },
+                StarKindTuning {
+                    kind: StarKind::Gold,
+                    weight: 2,
+                    value: 5,
+                    lifetime_secs: None,
+                },
+                StarKindTuning {
+                    kind: StarKind::Fading,
+                    weight: 3,
+                    value: 2,
+                    lifetime_secs: Some(4.0),
+                },
+                StarKindTuning {
+                    kind: StarKind::PowerUp,
+                    weight: 1,
+                    value: 1,
+                    lifetime_secs: None,
+                },
+            ],
+        }
+    }
+}
+
+/// A star kind, how often it is picked and what it is worth.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StarKindTuning {
+    pub kind: StarKind,
+    pub weight: u32,
+    /// Stars added to the player's count when collected.
+    pub value: u32,
+    /// Seconds before the star fades away, or `None` to stay until collected.
+    pub lifetime_secs: Option<f32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MissileTuning {
+    pub speed: f32,
+    /// Seconds before a missile that hit nothing disappears.
+    pub lifetime_secs: f32,
+    /// Times a missile bounces off the arena walls. It is destroyed on the
+    /// next wall it touches.
+    pub ricochets: u32,
+}
+
+impl Default for MissileTuning {
+    fn default() -> Self {
+        MissileTuning {
+            speed: 1000.0,
+            lifetime_secs: 3.0,
+            ricochets: 0,
+        }
+    }
+}
+
+/// Bonus handed out every `every_stars` stars.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RewardTuning {
+    pub every_stars: u32,
+    pub speed_multiplier: f32,
+    pub missiles: f32,
+}
+
+impl Default for RewardTuning {
+    fn default() -> Self {
+        RewardTuning {
+            every_stars: 10,
+            speed_multiplier: 1.1,
+            missiles: 10.0,
+        }
+    }
+}
+
+/// How holes get harder from one wave to the next. `holes` holds the
+/// numbers for wave 1.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaveTuning {
+    /// A wave lasts this many seconds or `stars` stars, whichever comes first.
+    pub secs: f32,
+    pub stars: u32,
+    /// Pause between waves while the banner shows. No holes spawn.
+    pub break_secs: f32,
+    /// Each wave multiplies the hole spawn interval by this, down to `min_spawn_secs`.
+    pub spawn_secs_multiplier: f32,
+    pub min_spawn_secs: f32,
+    /// Added to the hole speed each wave.
+    pub speed_step: f32,
+    /// Added to the hole cap each wave, up to `max_holes_limit`.
+    pub max_holes_step: usize,
+    pub max_holes_limit: usize,
+    pub kinds: Vec<HoleKindTuning>,
+}
+
+impl Default for WaveTuning {
+    fn default() -> Self {
+        WaveTuning {
+            secs: 45.0,
+            stars: 10,
+            break_secs: 2.0,
+            spawn_secs_multiplier: 0.9,
+            min_spawn_secs: 0.8,
+            speed_step: 20.0,
+            max_holes_step: 1,
+            max_holes_limit: 16,
+            kinds: vec![
+                HoleKindTuning {
+                    kind: HoleKind::Normal,
+                    from_wave: 1,
+                    weight: 6,
+                    speed_multiplier: 1.0,
+                    scale: 1.0,
+                },
+                HoleKindTuning {
+                    kind: HoleKind::Fast,
+                    from_wave: 3,
+                    weight: 2,
+                    speed_multiplier: 1.6,
+                    scale: 0.75,
+                },
+                HoleKindTuning {
+                    kind: HoleKind::Well,
+                    from_wave: 4,
+                    weight: 1,
+                    speed_multiplier: 0.5,
+                    scale: 1.0,
+                },
+                HoleKindTuning {
+                    kind: HoleKind::Big,
+                    from_wave: 5,
+                    weight: 1,
+                    speed_multiplier: 0.6,
+                    scale: 1.5,
+                },
+            ],
+        }
+    }
+}
+
+/// A hole kind, the first wave it spawns in and how often it is picked.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HoleKindTuning {
+    pub kind: HoleKind,
+    pub from_wave: u32,
+    pub weight: u32,
+    pub speed_multiplier: f32,
+    /// Size relative to a normal hole.
+    pub scale: f32,
+}
+
+/// Timed effects granted by power-up stars.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerUpTuning {
+    /// How long each power-up lasts.
+    pub secs: f32,
+    pub speed_multiplier: f32,
+    /// Angle in degrees between the missiles of a spread shot.
+    pub spread_degrees: f32,
+    /// Stars closer than this drift towards a player with the magnet.
+    pub magnet_radius: f32,
+    pub magnet_speed: f32,
+}
+
+impl Default for PowerUpTuning {
+    fn default() -> Self {
+        PowerUpTuning {
+            secs: 8.0,
+            speed_multiplier: 1.5,
+            spread_degrees: 15.0,
+            magnet_radius: 250.0,
+            magnet_speed: 400.0,
+        }
+    }
+}
+
+/// Points per pickup and kill, before the combo multiplier.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreTuning {
+    /// Points for each star a pickup is worth.
+    pub star_points: u32,
+    pub hole_points: u32,
+    /// A hole shot from this far away scores double, twice as far triple.
+    pub hole_distance_scale: f32,
+    /// Picking up a star within this many seconds of the last raises the combo.
+    /// Each time it runs out without one, the combo drops by one.
+    pub combo_secs: f32,
+    pub max_combo: u32,
+}
+
+impl Default for ScoreTuning {
+    fn default() -> Self {
+        ScoreTuning {
+            star_points: 10,
+            hole_points: 50,
+            hole_distance_scale: 500.0,
+            combo_secs: 2.0,
+            max_combo: 8,
+        }
+    }
+}
+
+// LOADER -------------------------------------
+
+#[derive(Default)]
+struct TuningLoader;
+
+#[derive(Debug, Error)]
+enum TuningLoaderError {
+    #[error("could not read tuning file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse tuning file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = TuningLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes::<Tuning>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+// RESOURCES -------------------------------------
+
+#[derive(Resource)]
+pub struct TuningHandle(Handle<Tuning>);
+
+// SYSTEMS -------------------------------------
+
+fn load_tuning(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+fn apply_tuning(
+    mut events: EventReader<AssetEvent<Tuning>>,
+    handle: Res<TuningHandle>,
+    assets: Res<Assets<Tuning>>,
+    mut tuning: ResMut<Tuning>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            if *id != handle.0.id() {
+                continue;
+            }
+            if let Some(loaded) = assets.get(*id) {
+                info!("Applied tuning from {}", TUNING_PATH);
+                *tuning = loaded.clone();
+            }
+        }
+    }
+}
+
+/// Run condition that holds once the tuning file has loaded, or has failed
+/// to and the defaults stay in place.
+pub fn tuning_ready(handle: Option<Res<TuningHandle>>, asset_server: Res<AssetServer>) -> bool {
+    handle.is_some_and(|handle| {
+        matches!(
+            asset_server.load_state(&handle.0),
+            LoadState::Loaded | LoadState::Failed(_)
+        )
+    })
+}
+
+// PLUGIN -------------------------------------
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tuning>()
+            .init_asset::<Tuning>()
+            .init_asset_loader::<TuningLoader>()
+            .add_systems(Startup, load_tuning)
+            .add_systems(
+                PreUpdate,
+                apply_tuning.run_if(resource_exists::<TuningHandle>),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hole_spawn_secs_shrinks_each_wave_down_to_the_floor() {
+        let tuning = Tuning::default();
+        assert_eq!(tuning.hole_spawn_secs(1), tuning.holes.spawn_secs);
+        assert!(tuning.hole_spawn_secs(2) < tuning.hole_spawn_secs(1));
+        assert_eq!(tuning.hole_spawn_secs(100), tuning.waves.min_spawn_secs);
+    }
+
+    #[test]
+    fn max_holes_grows_each_wave_up_to_the_limit() {
+        let tuning = Tuning::default();
+        assert_eq!(tuning.max_holes(1), tuning.holes.max_holes);
+        assert_eq!(
+            tuning.max_holes(3),
+            tuning.holes.max_holes + 2 * tuning.waves.max_holes_step
+        );
+        assert_eq!(tuning.max_holes(100), tuning.waves.max_holes_limit);
+    }
+
+    #[test]
+    fn tuning_file_parses() {
+        let text = include_str!("../../assets/game.tuning.ron");
+        if let Err(err) = ron::from_str::<Tuning>(text) {
+            panic!("assets/game.tuning.ron: {err}");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+
+use crate::{
+    player::Player,
+    tuning::{Tuning, WaveTuning},
+    AppState, GameState,
+};
+
+// EVENTS -------------------------------------
+
+#[derive(Event)]
+pub struct WaveStarted(pub u32);
+
+// COMPONENTS -------------------------------------
+
+#[derive(Component)]
+struct WaveBanner;
+
+// RESOURCES -------------------------------------
+
+/// The wave the run is on. Hole numbers for it come from `Tuning`.
+#[derive(Resource, Debug)]
+pub struct Wave {
+    pub number: u32,
+    timer: Timer,
+    stars_at_start: u32,
+    /// Runs between waves. Holes don't spawn until it finishes.
+    intermission: Timer,
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Wave::new(1, &WaveTuning::default(), 0)
+    }
+}
+
+impl Wave {
+    fn new(number: u32, tuning: &WaveTuning, stars: u32) -> Self {
+        Wave {
+            number,
+            timer: Timer::from_seconds(tuning.secs, TimerMode::Once),
+            stars_at_start: stars,
+            intermission: Timer::from_seconds(tuning.break_secs, TimerMode::Once),
+        }
+    }
+
+    pub fn spawning(&self) -> bool {
+        self.intermission.finished()
+    }
+}
+
+// SYSTEMS -------------------------------------
+
+fn reset_wave(tuning: Res<Tuning>, mut wave: ResMut<Wave>, mut events: EventWriter<WaveStarted>) {
+    *wave = Wave::new(1, &tuning.waves, 0);
+    events.send(WaveStarted(1));
+}
+
+fn advance_wave(
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    mut wave: ResMut<Wave>,
+    players: Query<&Player>,
+    mut events: EventWriter<WaveStarted>,
+) {
+    if !wave.intermission.tick(time.delta()).finished() {
+        return;
+    }
+    let stars: u32 = players.iter().map(|player| player.stars).sum();
+    let stars_this_wave = stars.saturating_sub(wave.stars_at_start);
+    if wave.timer.tick(time.delta()).finished() || stars_this_wave >= tuning.waves.stars {
+        let number = wave.number + 1;
+        *wave = Wave::new(number, &tuning.waves, stars);
+        events.send(WaveStarted(number));
+    }
+}
+
+fn show_wave_banner(
+    mut commands: Commands,
+    mut events: EventReader<WaveStarted>,
+    banners: Query<Entity, With<WaveBanner>>,
+) {
+    let Some(WaveStarted(number)) = events.read().last() else {
+        return;
+    };
+    for banner in banners.iter() {
+        commands.entity(banner).despawn_recursive();
+    }
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                top: Val::Percent(35.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            WaveBanner,
+            StateScoped(AppState::InGame),
+        ))
+        .with_child((
+            Text::new(format!("Wave {number}")),
+            TextFont {
+                font_size: 60.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+}
+
+fn hide_wave_banner(
+    mut commands: Commands,
+    wave: Res<Wave>,
+    banners: Query<Entity, With<WaveBanner>>,
+) {
+    if wave.spawning() {
+        for banner in banners.iter() {
+            commands.entity(banner).despawn_recursive();
+        }
+    }
+}
+
+// PLUGIN -------------------------------------
+
+/// Splits a run into waves that spawn more, faster and new kinds of holes,
+/// with a short break and a "Wave N" banner between them.
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wave>()
+            .add_event::<WaveStarted>()
+            .add_systems(OnEnter(GameState::Start), reset_wave)
+            .add_systems(
+                FixedUpdate,
+                advance_wave.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (show_wave_banner, hide_wave_banner)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}